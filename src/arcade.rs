@@ -0,0 +1,147 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+use serde::{Deserialize, Serialize};
+use strum_macros::Display;
+
+use crate::{
+    GameState,
+    storage,
+    settings::Settings,
+    helpers::despawn_with,
+    localization::Localization,
+    assets::theme::UiTheme,
+    ui::controls::{ButtonPressEvent, menu::CMUIMenu, button::CMUIButton},
+};
+
+/// Keeps [`ArcadeWallet`] and runs the prize counter screen,
+/// where tickets won in the arcade mode are exchanged for themes
+#[derive(Default)]
+pub struct ArcadePlugin;
+
+impl Plugin for ArcadePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(ArcadeWallet::load())
+            .add_enter_system(GameState::PrizeCounter, prize_counter_menu_system)
+            .add_system(handle_menu_click_system.run_in_state(GameState::PrizeCounter))
+            .add_exit_system(GameState::PrizeCounter, despawn_with::<CMUIMenu>);
+    }
+}
+
+/// Credits and tickets of the arcade mode and prizes bought with them,
+/// persisted in the user data directory
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct ArcadeWallet {
+    pub credits: u32,
+    pub tickets: u32,
+    /// Codes of the themes bought at the prize counter
+    pub unlocked: Vec<String>,
+}
+
+impl ArcadeWallet {
+    const FILE_NAME: &'static str = "arcade.ron";
+
+    fn load() -> Self {
+        storage::load(ArcadeWallet::FILE_NAME).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        storage::save(ArcadeWallet::FILE_NAME, self);
+    }
+
+    /// Exchanges tickets for the theme, returns `false` if there are not enough of them
+    fn buy(&mut self, theme: &UiTheme) -> bool {
+        let price = theme.price.unwrap_or_default();
+
+        if self.unlocked.contains(&theme.code) || self.tickets < price {
+            return false;
+        }
+
+        self.tickets -= price;
+        self.unlocked.push(theme.code.clone());
+
+        true
+    }
+}
+
+#[derive(Display)]
+enum PrizeCounterButton {
+    Back
+}
+
+/// Themes with a price, cheapest first
+fn prizes(themes: &Assets<UiTheme>) -> Vec<&UiTheme> {
+    let mut prizes: Vec<&UiTheme> = themes
+        .iter()
+        .map(|(_, theme)| theme)
+        .filter(|theme| theme.price.is_some())
+        .collect();
+
+    prizes.sort_by(|a, b| a.price.cmp(&b.price).then_with(|| a.code.cmp(&b.code)));
+
+    prizes
+}
+
+fn prize_counter_menu(wallet: &ArcadeWallet, themes: &Assets<UiTheme>, localization: &Localization) -> CMUIMenu {
+    let mut buttons: Vec<CMUIButton> = prizes(themes)
+        .into_iter()
+        .map(|theme| {
+            let price = theme.price.unwrap_or_default();
+            let is_owned = wallet.unlocked.contains(&theme.code);
+
+            CMUIButton::new(&theme.code, &theme.name)
+                .disabled(is_owned || wallet.tickets < price)
+                .with_description(if is_owned {
+                    localization.get("prize_counter.owned")
+                } else {
+                    localization.plural("prize_counter.price", price as u64, &[])
+                })
+        })
+        .collect();
+
+    buttons.push(CMUIButton::new(PrizeCounterButton::Back, "ui.back"));
+
+    let first = buttons.iter().position(|button| !button.is_disabled).unwrap_or(buttons.len() - 1);
+
+    buttons[first] = buttons[first].clone().selected();
+
+    CMUIMenu::new(localization.plural("prize_counter.title", wallet.tickets as u64, &[]), buttons)
+}
+
+fn prize_counter_menu_system(
+    wallet: Res<ArcadeWallet>,
+    themes: Res<Assets<UiTheme>>,
+    localization: Res<Localization>,
+    mut commands: Commands,
+) {
+    commands.spawn()
+        .insert(prize_counter_menu(&wallet, &themes, &localization));
+}
+
+/// Bought theme is applied right away
+fn handle_menu_click_system(
+    themes: Res<Assets<UiTheme>>,
+    localization: Res<Localization>,
+    mut wallet: ResMut<ArcadeWallet>,
+    mut settings: ResMut<Settings>,
+    mut events: EventReader<ButtonPressEvent>,
+    mut menu_query: Query<&mut CMUIMenu>,
+    mut commands: Commands,
+) {
+    for event in events.iter() {
+        if event.0 == PrizeCounterButton::Back.to_string() {
+            commands.insert_resource(NextState(GameState::MainMenu));
+        } else if let Some(theme) = themes.iter().map(|(_, theme)| theme).find(|theme| theme.code == event.0) {
+            if !wallet.buy(theme) { continue; }
+
+            wallet.save();
+            settings.theme = theme.code.clone();
+            settings.save();
+
+            for mut menu in menu_query.iter_mut() {
+                *menu = prize_counter_menu(&wallet, &themes, &localization);
+            }
+        }
+    }
+}
@@ -1,9 +1,13 @@
-use bevy::{prelude::*, utils::HashMap};
+use bevy::{prelude::*, asset::LoadState, utils::HashMap};
+use iyes_loopless::prelude::*;
 use bevy_kira_audio::{AudioSource, AudioApp, AudioChannel};
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 use strum::IntoEnumIterator;
 use strum_macros::{Display, EnumIter};
 
+use crate::{GameState, settings::Settings};
+
 use super::AssetsLoading;
 
 #[derive(Default)]
@@ -14,6 +18,15 @@ pub struct GlassAudioChannel;
 pub struct DropAudioChannel;
 pub struct UiAudioChannel;
 
+impl BackgroundAudioChannel {
+    /// Heartbeat is played louder than music
+    pub const HEARTBEAT_GAIN: f32 = 1.5;
+}
+
+impl DropAudioChannel {
+    pub const GAIN: f32 = 1.5;
+}
+
 impl Plugin for AudioLoaderPlugin {
     fn build(&self, app: &mut App) {
         app
@@ -22,7 +35,8 @@ impl Plugin for AudioLoaderPlugin {
             .add_audio_channel::<GlassAudioChannel>()
             .add_audio_channel::<DropAudioChannel>()
             .add_audio_channel::<UiAudioChannel>()
-            .add_startup_system(load_assets_system);
+            .add_startup_system(load_assets_system)
+            .add_exit_system(GameState::Loading, drop_failed_assets_system);
     }
 }
 
@@ -30,14 +44,19 @@ impl Plugin for AudioLoaderPlugin {
 pub struct AudioHandleStorage(pub HashMap<AudioCollection, Handle<AudioSource>>);
 
 impl AudioHandleStorage {
-    pub fn get_random(&self, collection: &[AudioCollection]) -> Option<&Handle<AudioSource>> {
-        let sound = &collection[rand::thread_rng().gen_range(0..collection.len())];
+    /// Picks a random sound of the collection. Pass [`GameRng::cosmetic`](crate::rng::GameRng::cosmetic)
+    /// stream as `rng` so sound choice doesn't affect gameplay
+    pub fn get_random(&self, collection: &[AudioCollection], rng: &mut impl Rng) -> Option<&Handle<AudioSource>> {
+        if collection.is_empty() { return None; }
+
+        let sound = &collection[rng.gen_range(0..collection.len())];
 
         self.0.get(sound)
     }
 }
 
-#[derive(PartialEq, Eq, Hash, Display, EnumIter, Clone, Copy)]
+/// Sounds are optional, missing ones are left out of [`AudioHandleStorage`] and not played
+#[derive(PartialEq, Eq, Hash, Debug, Display, EnumIter, Clone, Copy, Deserialize, Serialize)]
 pub enum AudioCollection {
     Background1,
 
@@ -80,11 +99,17 @@ fn load_assets_system(
         );
     }
 
-    assets_loading.add_storage(&asset_storage.0);
+    assets_loading.add_storage(&asset_storage.0, false);
+}
+
+fn drop_failed_assets_system(asset_server: Res<AssetServer>, mut asset_storage: ResMut<AudioHandleStorage>) {
+    asset_storage.0.retain(|_, handle| asset_server.get_load_state(&*handle) != LoadState::Failed);
 }
 
 pub fn stop_background_audio_system(
+    settings: Res<Settings>,
     audio_background: Res<AudioChannel<BackgroundAudioChannel>>,
 ) {
     audio_background.stop();
+    audio_background.set_volume(settings.music_volume());
 }
\ No newline at end of file
@@ -0,0 +1,146 @@
+use bevy::{
+    prelude::*,
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    reflect::TypeUuid,
+    utils::BoxedFuture
+};
+use serde::{Deserialize, Serialize};
+
+use crate::localization::Localization;
+
+use super::{AssetsLoading, audio::AudioCollection};
+
+#[derive(Default)]
+pub struct GamemodeLoaderPlugin;
+
+impl Plugin for GamemodeLoaderPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<GamemodeHandleStorage>()
+            .add_asset::<GamemodeAsset>()
+            .init_asset_loader::<GamemodeAssetLoader>()
+            .add_startup_system(load_assets_system);
+    }
+}
+
+/// Gamemode variant described in `assets/gamemodes/*.gamemode.ron`
+#[derive(Deserialize, Serialize, TypeUuid, Clone)]
+#[uuid = "922a4cf8-cbe5-4b44-99f0-70b72cd00f43"]
+pub struct GamemodeAsset {
+    /// Localization key of the main menu button text
+    pub label: String,
+    pub scoring: ScoringRule,
+    /// Seconds given for the round
+    pub time_limit: f32,
+    /// Minimal number of caught toys to win
+    pub win_threshold: u8,
+    /// Background music is picked randomly from this pool
+    pub music: Vec<AudioCollection>,
+    /// Seconds before the end of the round when heartbeat starts to play
+    pub heartbeat_warning: Option<f32>,
+    /// Seconds added to the clock for every caught toy, used by [`ScoringRule::Survival`]
+    #[serde(default)]
+    pub catch_bonus: f32,
+    /// Seconds taken from the clock for catching a toy other than the target, used by [`ScoringRule::TargetCatches`]
+    #[serde(default)]
+    pub miss_penalty: f32,
+    /// Credits a player gets in the arcade mode when the wallet is empty, one is spent per attempt
+    #[serde(default)]
+    pub credits: u32,
+    /// Tickets toys are worth in the arcade mode, every toy gets a random one
+    #[serde(default)]
+    pub ticket_values: Vec<u32>,
+    /// Localization key of the rules shown under the menu, generated from the scoring rule if empty
+    #[serde(default)]
+    pub description: String,
+    /// Id of the variant that has to be won before this one can be played
+    #[serde(default)]
+    pub requires: Option<String>,
+}
+
+impl GamemodeAsset {
+    pub fn description(&self, localization: &Localization) -> String {
+        if !self.description.is_empty() {
+            return localization.get(&self.description);
+        }
+
+        match self.scoring {
+            ScoringRule::TimeToFirstCatch => localization.format(
+                "gamemode.rules.time_to_first_catch",
+                &[("time", &self.time_limit)]
+            ),
+            ScoringRule::CountCatches => localization.plural(
+                "gamemode.rules.count_catches",
+                self.win_threshold as u64,
+                &[("time", &self.time_limit)]
+            ),
+            ScoringRule::TargetCatches => localization.plural(
+                "gamemode.rules.target_catches",
+                self.win_threshold as u64,
+                &[("time", &self.time_limit), ("penalty", &self.miss_penalty)]
+            ),
+            ScoringRule::Tickets => localization.plural(
+                "gamemode.rules.tickets",
+                self.credits as u64,
+                &[("time", &self.time_limit)]
+            ),
+            ScoringRule::Survival => localization.plural(
+                "gamemode.rules.survival",
+                self.win_threshold as u64,
+                &[("time", &self.time_limit), ("bonus", &self.catch_bonus)]
+            ),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScoringRule {
+    /// Single attempt, the result is how fast the toy was caught
+    TimeToFirstCatch,
+    /// Unlimited attempts, the result is how many toys were caught in time
+    CountCatches,
+    /// Only the highlighted toy counts and others cost time, the result is how many targets were caught in time
+    TargetCatches,
+    /// Every catch adds time to the clock, the result is how long the round lasted
+    Survival,
+    /// Every attempt costs a credit, the result is how many tickets the caught toys are worth
+    Tickets,
+}
+
+#[derive(Default)]
+pub struct GamemodeHandleStorage(pub Vec<Handle<GamemodeAsset>>);
+
+#[derive(Default)]
+struct GamemodeAssetLoader;
+
+impl AssetLoader for GamemodeAssetLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let gamemode = ron::de::from_bytes::<GamemodeAsset>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(gamemode));
+
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["gamemode.ron"]
+    }
+}
+
+fn load_assets_system(
+    asset_server: Res<AssetServer>,
+    mut asset_storage: ResMut<GamemodeHandleStorage>,
+    mut assets_loading: ResMut<AssetsLoading>,
+) {
+    let handles = asset_server.load_folder("gamemodes").expect("Gamemodes folder is missing");
+
+    for handle in handles {
+        assets_loading.add(handle.clone(), true);
+        asset_storage.0.push(handle.typed());
+    }
+}
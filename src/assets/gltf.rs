@@ -16,7 +16,7 @@ impl Plugin for GltfLoaderPlugin {
 #[derive(Default)]
 pub struct GltfHandleStorage(pub HashMap<GltfCollection, Handle<Gltf>>);
 
-#[derive(PartialEq, Eq, Hash)]
+#[derive(PartialEq, Eq, Hash, Debug)]
 pub enum GltfCollection {
     ClawMachine,
     Room,
@@ -32,5 +32,5 @@ fn load_assets_system(
     asset_storage.0.insert(GltfCollection::Room, asset_server.load("models/licensed/kleeblatt_nosky.glb"));
     asset_storage.0.insert(GltfCollection::HighLander, asset_server.load("models/licensed/caucasian_highlander.glb"));
 
-    assets_loading.add_storage(&asset_storage.0);
+    assets_loading.add_storage(&asset_storage.0, true);
 }
\ No newline at end of file
@@ -0,0 +1,95 @@
+use bevy::{
+    prelude::*,
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    reflect::TypeUuid,
+    utils::{BoxedFuture, HashMap},
+};
+use serde::Deserialize;
+
+use super::AssetsLoading;
+
+#[derive(Default)]
+pub struct LocaleLoaderPlugin;
+
+impl Plugin for LocaleLoaderPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<LocaleHandleStorage>()
+            .add_asset::<LocaleAsset>()
+            .init_asset_loader::<LocaleAssetLoader>()
+            .add_startup_system(load_assets_system);
+    }
+}
+
+/// String table of a language described in `assets/locales/<code>.locale.ron`
+#[derive(Deserialize, TypeUuid)]
+#[uuid = "5b0f4e2c-8d7a-4c1e-9f3b-2a6d1c8e7f40"]
+pub struct LocaleAsset {
+    /// Language code taken from the file name, e.g. `en`
+    #[serde(skip)]
+    pub code: String,
+    /// Name of the language in itself, shown in settings
+    pub name: String,
+    #[serde(default)]
+    pub strings: HashMap<String, String>,
+    /// Strings that depend on a number, by plural category
+    #[serde(default)]
+    pub plurals: HashMap<String, HashMap<PluralCategory, String>>,
+}
+
+/// CLDR plural categories, which of them are used depends on the language
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PluralCategory {
+    Zero,
+    One,
+    Two,
+    Few,
+    Many,
+    Other,
+}
+
+#[derive(Default)]
+pub struct LocaleHandleStorage(pub Vec<Handle<LocaleAsset>>);
+
+#[derive(Default)]
+struct LocaleAssetLoader;
+
+impl AssetLoader for LocaleAssetLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let mut locale = ron::de::from_bytes::<LocaleAsset>(bytes)?;
+
+            locale.code = load_context
+                .path()
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.split('.').next())
+                .unwrap_or_default()
+                .to_string();
+            load_context.set_default_asset(LoadedAsset::new(locale));
+
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["locale.ron"]
+    }
+}
+
+fn load_assets_system(
+    asset_server: Res<AssetServer>,
+    mut asset_storage: ResMut<LocaleHandleStorage>,
+    mut assets_loading: ResMut<AssetsLoading>,
+) {
+    let handles = asset_server.load_folder("locales").expect("Locales folder is missing");
+
+    for handle in handles {
+        assets_loading.add(handle.clone(), true);
+        asset_storage.0.push(handle.typed());
+    }
+}
@@ -1,29 +1,109 @@
-use std::hash::Hash;
+use std::fmt::Debug;
 
 use bevy::{prelude::*, app::PluginGroupBuilder, asset::{LoadState, Asset}, utils::HashMap};
 use iyes_loopless::prelude::*;
 
 use crate::GameState;
 
-use self::{gltf::GltfLoaderPlugin, audio::AudioLoaderPlugin};
+use self::{
+    gltf::GltfLoaderPlugin,
+    audio::AudioLoaderPlugin,
+    gamemode::GamemodeLoaderPlugin,
+    locale::LocaleLoaderPlugin,
+    theme::ThemeLoaderPlugin,
+};
 
 pub mod gltf;
 pub mod audio;
+pub mod gamemode;
+pub mod locale;
+pub mod theme;
 
 #[derive(Default)]
 pub struct AssetLoaderPlugins;
 #[derive(Default)]
 pub struct AssetLoaderPlugin;
+
+/// Assets the game waits for in [`GameState::Loading`]
 #[derive(Default)]
-pub struct AssetsLoading(pub Vec<HandleUntyped>);
+pub struct AssetsLoading(pub Vec<LoadingAsset>);
+
+pub struct LoadingAsset {
+    pub handle: HandleUntyped,
+    /// Key of the handle storage the asset belongs to, e.g. `Drop1` of [`AudioCollection`](audio::AudioCollection)
+    pub collection: Option<String>,
+    /// Failure of an essential asset stops loading, others are skipped
+    pub is_essential: bool,
+}
+
+/// Load state of [`AssetsLoading`]
+pub struct LoadingStatus<'a> {
+    /// Share of finished assets in range 0.0..=1.0
+    pub progress: f32,
+    pub pending: Option<&'a LoadingAsset>,
+    /// Essential assets that failed to load
+    pub failed: Vec<&'a LoadingAsset>,
+    /// Optional assets that failed to load
+    pub skipped: Vec<&'a LoadingAsset>,
+}
 
 impl AssetsLoading {
-    fn add_storage<C, A>(&mut self, handle_storage: &HashMap<C, Handle<A>>)
+    pub fn add(&mut self, handle: HandleUntyped, is_essential: bool) {
+        self.0.push(LoadingAsset { handle, collection: None, is_essential });
+    }
+
+    fn add_storage<C, A>(&mut self, handle_storage: &HashMap<C, Handle<A>>, is_essential: bool)
     where
-        C: Hash,
+        C: Debug,
         A: Asset,
     {
-        handle_storage.iter().for_each(|(_, value)| self.0.push(value.clone_untyped()));
+        for (key, handle) in handle_storage.iter() {
+            self.0.push(LoadingAsset {
+                handle: handle.clone_untyped(),
+                collection: Some(format!("{:?}", key)),
+                is_essential,
+            });
+        }
+    }
+
+    pub fn status(&self, server: &AssetServer) -> LoadingStatus {
+        let mut status = LoadingStatus { progress: 1.0, pending: None, failed: Vec::new(), skipped: Vec::new() };
+        let mut finished = 0;
+
+        for asset in self.0.iter() {
+            match server.get_load_state(&asset.handle) {
+                LoadState::Loaded => finished += 1,
+                LoadState::Failed if asset.is_essential => status.failed.push(asset),
+                LoadState::Failed => {
+                    finished += 1;
+                    status.skipped.push(asset);
+                }
+                _ => if status.pending.is_none() {
+                    status.pending = Some(asset);
+                },
+            }
+        }
+
+        if !self.0.is_empty() {
+            status.progress = finished as f32 / self.0.len() as f32;
+        }
+
+        status
+    }
+}
+
+impl LoadingAsset {
+    /// Collection key and path of the file, e.g. `Drop1 (audio/drop1.ogg)`
+    pub fn describe(&self, server: &AssetServer) -> String {
+        let path = server
+            .get_handle_path(&self.handle)
+            .map(|path| path.path().display().to_string())
+            .unwrap_or_default();
+
+        match &self.collection {
+            Some(collection) => format!("{} ({})", collection, path),
+            None => path,
+        }
     }
 }
 
@@ -32,7 +112,10 @@ impl PluginGroup for AssetLoaderPlugins {
         group
             .add(AssetLoaderPlugin)
             .add(GltfLoaderPlugin)
-            .add(AudioLoaderPlugin);
+            .add(AudioLoaderPlugin)
+            .add(GamemodeLoaderPlugin)
+            .add(LocaleLoaderPlugin)
+            .add(ThemeLoaderPlugin);
     }
 }
 
@@ -46,24 +129,24 @@ impl Plugin for AssetLoaderPlugin {
     }
 }
 
+/// Leaves [`GameState::Loading`] when every asset is loaded or skipped.
+/// Failed essential assets keep the game here until they are loaded again
 fn check_assets_ready_system(
     server: Res<AssetServer>,
     loading: Res<AssetsLoading>,
     mut commands: Commands
 ) {
-    match server.get_group_load_state(loading.0.iter().map(|handle| handle.id)) {
-        LoadState::Failed => {
-            panic!("Some asset failed to load!")
-        }
-        LoadState::Loaded => {
-            commands.insert_resource(NextState(GameState::MainMenu));
+    let status = loading.status(&server);
 
-            commands.remove_resource::<AssetsLoading>();
-            // (note: if you don't have any other handles to the assets
-            // elsewhere, they will get unloaded after this)
-        }
-        _ => {
-            // NotLoaded/Loading: not fully ready yet
-        }
+    if status.pending.is_some() || !status.failed.is_empty() { return; }
+
+    for asset in status.skipped {
+        warn!("Optional asset {} failed to load and is skipped", asset.describe(&server));
     }
+
+    commands.insert_resource(NextState(GameState::MainMenu));
+
+    commands.remove_resource::<AssetsLoading>();
+    // (note: if you don't have any other handles to the assets
+    // elsewhere, they will get unloaded after this)
 }
\ No newline at end of file
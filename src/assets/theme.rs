@@ -0,0 +1,236 @@
+use bevy::{
+    prelude::*,
+    asset::{AssetLoader, AssetPath, LoadContext, LoadedAsset},
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+
+use crate::{constants::PURPLE_COLOR, settings::{Accessibility, SelectionPalette}};
+
+use super::AssetsLoading;
+
+#[derive(Default)]
+pub struct ThemeLoaderPlugin;
+
+impl Plugin for ThemeLoaderPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<ThemeHandleStorage>()
+            .add_asset::<UiTheme>()
+            .init_asset_loader::<UiThemeLoader>()
+            .add_startup_system(load_assets_system);
+    }
+}
+
+/// Look of the CMUI controls described in `assets/themes/<code>.theme.ron`.
+/// The one picked in settings is kept as a resource, which spawners read,
+/// with [`Accessibility`] options applied. Its sizes are already scaled
+#[derive(Deserialize, TypeUuid, Clone)]
+#[uuid = "0c6a7e3d-41f2-4b8e-a5d9-6f1e2b3c4d50"]
+pub struct UiTheme {
+    /// Theme code taken from the file name, e.g. `default`
+    #[serde(skip)]
+    pub code: String,
+    /// Localization key of the name shown in settings
+    pub name: String,
+    /// Tickets the theme costs at the prize counter of the arcade mode, free if not set
+    #[serde(default)]
+    pub price: Option<u32>,
+    /// Paths of the fonts relative to the assets folder
+    pub fonts: ThemeFonts,
+    #[serde(skip)]
+    pub font: Handle<Font>,
+    #[serde(skip)]
+    pub header_font: Handle<Font>,
+    /// Text and the front layer of headers
+    pub text_color: Color,
+    /// Back layer of button texts and headers
+    pub shadow_color: Color,
+    /// Background of the focused button, highlights of sliders and character grid
+    pub active_color: Color,
+    /// Background of buttons without focus
+    pub normal_color: Color,
+    /// Solid panel behind menus and big texts, high contrast option adds it to any theme
+    #[serde(default)]
+    pub panel_color: Option<Color>,
+    /// Bar next to the focused button, set by colorblind palettes
+    #[serde(skip)]
+    pub marker_color: Option<Color>,
+    pub disabled_text_color: Color,
+    pub disabled_shadow_color: Color,
+    pub button_width: f32,
+    pub button_height: f32,
+    pub button_font_size: f32,
+    pub title_font_size: f32,
+    pub description_font_size: f32,
+    /// Text of screens like high score table and key bindings
+    pub text_font_size: f32,
+    /// Big text in the middle of the screen, e.g. the rank of a new record
+    pub in_game_font_size: f32,
+    /// Countdown before the round
+    pub countdown_font_size: f32,
+    /// Options the theme has been adjusted with
+    #[serde(skip)]
+    pub accessibility: Accessibility,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct ThemeFonts {
+    pub text: String,
+    pub header: String,
+}
+
+impl UiTheme {
+    pub const DEFAULT: &'static str = "default";
+    const HIGH_CONTRAST_PANEL: Color = Color::rgba(0.0, 0.0, 0.0, 0.85);
+    pub const MARKER_WIDTH: f32 = 8.0;
+
+    /// Size in pixels multiplied by the UI scale, for sizes that don't come from the theme
+    pub fn px(&self, value: f32) -> Val {
+        Val::Px(value * self.accessibility.ui_scale)
+    }
+
+    /// Copy of the theme with scaled sizes, backing panel and selection colors of the options
+    pub fn with_accessibility(mut self, accessibility: &Accessibility) -> Self {
+        let scale = accessibility.ui_scale;
+
+        self.button_width *= scale;
+        self.button_height *= scale;
+        self.button_font_size *= scale;
+        self.title_font_size *= scale;
+        self.description_font_size *= scale;
+        self.text_font_size *= scale;
+        self.in_game_font_size *= scale;
+        self.countdown_font_size *= scale;
+
+        if accessibility.high_contrast {
+            self.panel_color = self.panel_color.or(Some(UiTheme::HIGH_CONTRAST_PANEL));
+        }
+
+        // Okabe-Ito colors, which stay distinct for the kind of color blindness
+        let (active, marker) = match accessibility.palette {
+            SelectionPalette::Default => (self.active_color, None),
+            SelectionPalette::Deuteranopia => (Color::rgb(0.0, 0.447, 0.698), Some(Color::rgb(0.902, 0.624, 0.0))),
+            SelectionPalette::Protanopia => (Color::rgb(0.0, 0.447, 0.698), Some(Color::rgb(0.941, 0.894, 0.259))),
+            SelectionPalette::Tritanopia => (Color::rgb(0.835, 0.369, 0.0), Some(Color::rgb(0.337, 0.706, 0.914))),
+        };
+
+        self.active_color = active;
+        self.marker_color = marker;
+        self.accessibility = accessibility.clone();
+
+        self
+    }
+
+    /// Color of the layer of a two layer text, the first one is the shadow
+    pub fn layer_color(&self, index: i32) -> Color {
+        if index == 0 { self.shadow_color } else { self.text_color }
+    }
+
+    /// Codes and names of the loaded themes that are free or bought, sorted by code
+    pub fn available(themes: &Assets<UiTheme>, unlocked: &[String]) -> Vec<(String, String)> {
+        let mut available: Vec<(String, String)> = themes
+            .iter()
+            .filter(|(_, theme)| theme.price.is_none() || unlocked.contains(&theme.code))
+            .map(|(_, theme)| (theme.code.clone(), theme.name.clone()))
+            .collect();
+
+        available.sort();
+
+        available
+    }
+}
+
+/// Used until the theme files are loaded, e.g. by the loading screen
+impl FromWorld for UiTheme {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.get_resource::<AssetServer>().unwrap();
+        let fonts = ThemeFonts {
+            text: "fonts/RussoOne-Regular.ttf".to_string(),
+            header: "fonts/Blaka-Regular.ttf".to_string(),
+        };
+
+        Self {
+            code: String::new(),
+            name: String::new(),
+            price: None,
+            font: asset_server.load(fonts.text.as_str()),
+            header_font: asset_server.load(fonts.header.as_str()),
+            fonts,
+            text_color: Color::ANTIQUE_WHITE,
+            shadow_color: PURPLE_COLOR,
+            active_color: PURPLE_COLOR,
+            normal_color: Color::NONE,
+            panel_color: None,
+            marker_color: None,
+            disabled_text_color: Color::GRAY,
+            disabled_shadow_color: Color::DARK_GRAY,
+            button_width: 280.0,
+            button_height: 65.0,
+            button_font_size: 40.0,
+            title_font_size: 100.0,
+            description_font_size: 24.0,
+            text_font_size: 30.0,
+            in_game_font_size: 150.0,
+            countdown_font_size: 200.0,
+            accessibility: default(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct ThemeHandleStorage(pub Vec<Handle<UiTheme>>);
+
+#[derive(Default)]
+struct UiThemeLoader;
+
+impl AssetLoader for UiThemeLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let mut theme = ron::de::from_bytes::<UiTheme>(bytes)?;
+
+            theme.code = load_context
+                .path()
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.split('.').next())
+                .unwrap_or_default()
+                .to_string();
+            theme.font = load_context.get_handle(theme.fonts.text.as_str());
+            theme.header_font = load_context.get_handle(theme.fonts.header.as_str());
+
+            let text_font = AssetPath::from(theme.fonts.text.as_str()).to_owned();
+            let header_font = AssetPath::from(theme.fonts.header.as_str()).to_owned();
+
+            load_context.set_default_asset(
+                LoadedAsset::new(theme)
+                    .with_dependency(text_font)
+                    .with_dependency(header_font)
+            );
+
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["theme.ron"]
+    }
+}
+
+fn load_assets_system(
+    asset_server: Res<AssetServer>,
+    mut asset_storage: ResMut<ThemeHandleStorage>,
+    mut assets_loading: ResMut<AssetsLoading>,
+) {
+    let handles = asset_server.load_folder("themes").expect("Themes folder is missing");
+
+    for handle in handles {
+        assets_loading.add(handle.clone(), true);
+        asset_storage.0.push(handle.typed());
+    }
+}
@@ -1,5 +1,7 @@
 use bevy::{prelude::*, input::mouse::{MouseMotion, MouseWheel, MouseScrollUnit}};
 
+use crate::settings::Settings;
+
 #[derive(Default)]
 pub struct CameraPlugin;
 
@@ -33,6 +35,7 @@ impl Default for PanOrbitCamera {
 /// Pan the camera with middle mouse click, zoom with scroll wheel, orbit with right mouse click.
 fn pan_orbit_camera_system(
     windows: Res<Windows>,
+    settings: Res<Settings>,
     mut ev_motion: EventReader<MouseMotion>,
     mut ev_scroll: EventReader<MouseWheel>,
     keyboard_input: ResMut<Input<KeyCode>>,
@@ -70,6 +73,9 @@ fn pan_orbit_camera_system(
             scroll += ev.y * touchpad_factor;
         }
     }
+    rotation_move *= settings.camera_sensitivity;
+    pan *= settings.camera_sensitivity;
+
     if input_mouse.just_released(orbit_button) || input_mouse.just_pressed(orbit_button) {
         orbit_button_changed = true;
     }
@@ -1,14 +1,15 @@
 use bevy::{prelude::{*, shape::Capsule}, ecs::event::Events};
-use bevy_kira_audio::AudioChannel;
 use bevy_rapier3d::prelude::*;
 use iyes_loopless::prelude::*;
 
 use crate::{
-    assets::audio::{AudioHandleStorage, AudioCollection, DropAudioChannel},
     glue::Glue,
-    movement::WASDMovement,
+    movement::{WASDMovement, AxisMovement, MovementAxis},
     constants::{COL_GROUP_EJECTED_TOY, COL_GROUP_TOY_EJECTION_SHELV, COL_GROUP_GLASS},
-    toy::ToySensor,
+    toy::{ToySensor, EjectedToy},
+    clock::GameClock,
+    controls::ClawInput,
+    settings::ControlScheme,
     GameState
 };
 
@@ -25,28 +26,54 @@ impl Plugin for ClawPlugin {
                 ConditionSet::new()
                     .run_in_state(GameState::InGame)
                     .with_system(claw_lift_sync_system)
-                    .with_system(release_claw_with_keyboard_system)
+                    .with_system(release_claw_system)
                     .with_system(claw_lift_activation_system.run_on_event::<ReleaseClawEvent>())
                     .with_system(claw_lift_system)
                     .with_system(claw_return_system)
                     .with_system(claw_manual_control_system)
+                    .with_system(claw_axis_control_system)
                     .with_system(claw_stopper_event_manager_system)
                     .with_system(claw_string_system)
                     .into()
-            );
+            )
+            .add_exit_system(GameState::InGame, reset_claw_system);
     }
 }
 
 pub struct ReleaseClawEvent;
-pub struct ToyCatchEvent;
+/// Claw has grabbed the toy
+pub struct ToyCatchEvent(pub Entity);
 pub struct ClawReturnedToBaseEvent;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ClawControllerState {
     Locked,
     Manual,
+    /// First step of [`ControlScheme::OneAxis`], `is_moved` is set once the input is held
+    MovingX { is_moved: bool },
+    /// Second step of [`ControlScheme::OneAxis`], the claw is released when the input is let go
+    MovingZ { is_moved: bool },
     ReturnToBase(Vec3)
 }
 
+impl ClawControllerState {
+    /// State in which the player gets the claw
+    pub fn controlled(scheme: ControlScheme) -> Self {
+        match scheme {
+            ControlScheme::Free => ClawControllerState::Manual,
+            ControlScheme::OneAxis => ClawControllerState::MovingX { is_moved: false },
+        }
+    }
+
+    /// Player moves the claw and it can be released
+    pub fn is_controlled(&self) -> bool {
+        matches!(
+            self,
+            ClawControllerState::Manual | ClawControllerState::MovingX { .. } | ClawControllerState::MovingZ { .. }
+        )
+    }
+}
+
 #[derive(Debug)]
 pub enum ClawLiftState {
     Off,
@@ -86,15 +113,6 @@ impl ClawString {
     pub const START_HEIGHT: f32 = 0.03;
 }
 
-const DROP_SFX: [AudioCollection; 6] = [
-    AudioCollection::Drop1,
-    AudioCollection::Drop2,
-    AudioCollection::Drop3,
-    AudioCollection::Drop4,
-    AudioCollection::Drop5,
-    AudioCollection::Drop6,
-];
-
 fn claw_lift_sync_system(
     claw_controller_query: Query<&Transform, With<ClawController>>,
     mut claw_lift_query: Query<&mut Transform, (With<ClawLift>, Without<ClawController>)>,
@@ -110,38 +128,39 @@ fn claw_lift_sync_system(
 }
 
 fn claw_lift_activation_system(
-    audio_drop: Res<AudioChannel<DropAudioChannel>>,
-    audio_storage: Res<AudioHandleStorage>,
     mut claw_lift_query: Query<&mut ClawLift>,
     mut claw_controller_query: Query<&mut ClawController>,
 ) {
     if let (Ok(mut claw_lift), Ok(mut claw_controller)) = (
         claw_lift_query.get_single_mut(), claw_controller_query.get_single_mut()
     ) {
-        if let ClawControllerState::Manual = claw_controller.0 {
-            if let Some(drop_sfx) = audio_storage.get_random(&DROP_SFX) {
-                audio_drop.set_volume(1.5);
-                audio_drop.play(drop_sfx.clone());
-            }
-    
+        if claw_controller.0.is_controlled() {
             claw_controller.0 = ClawControllerState::Locked;
             claw_lift.0 = ClawLiftState::Down;
         }
     }
 }
 
-fn release_claw_with_keyboard_system(
-    keyboard: Res<Input<KeyCode>>,
+/// One axis scheme has no release button, see [`claw_axis_control_system`]
+fn release_claw_system(
+    input: Res<ClawInput>,
+    claw_controller_query: Query<&ClawController>,
     mut events: EventWriter<ReleaseClawEvent>,
 ) {
-    if keyboard.just_pressed(KeyCode::Return) {
+    let is_one_axis = claw_controller_query
+        .get_single()
+        .map_or(false, |claw_controller| {
+            matches!(claw_controller.0, ClawControllerState::MovingX { .. } | ClawControllerState::MovingZ { .. })
+        });
+
+    if input.release && !is_one_axis {
         events.send(ReleaseClawEvent);
     }
 }
 
 
 fn claw_lift_system(
-    time: Res<Time>,
+    clock: Res<GameClock>,
     mut collision_events: EventReader<CollisionEvent>,
     mut toy_catch_events: EventWriter<ToyCatchEvent>,
     mut claw_lift_query: Query<(&mut ClawLift, &mut Transform)>,
@@ -157,7 +176,7 @@ fn claw_lift_system(
 
         match claw_lift.0 {
             ClawLiftState::Down => {
-                claw_lift_position.translation.y -= ClawLift::SPEED * time.delta_seconds();
+                claw_lift_position.translation.y -= ClawLift::SPEED * clock.delta_seconds();
 
                 if let Ok(claw_stopper) = claw_stopper_query.get_single() {
                     for event in collision_events.iter() {
@@ -171,7 +190,7 @@ fn claw_lift_system(
                                         && entities.into_iter().any(|entity| entity == &toy_sensor) {
                                         if let Ok(toy) = parent_query.get(toy_sensor) {
                                             commands.entity(claw_sensor).insert(Glue(toy.0));
-                                            toy_catch_events.send(ToyCatchEvent);
+                                            toy_catch_events.send(ToyCatchEvent(toy.0));
                                         }
                                     }
                                 }
@@ -188,14 +207,14 @@ fn claw_lift_system(
             },
             ClawLiftState::Wait(seconds_remain) => {
                 if seconds_remain > 0.0 {
-                    claw_lift.0 = ClawLiftState::Wait(seconds_remain - time.delta_seconds());
+                    claw_lift.0 = ClawLiftState::Wait(seconds_remain - clock.delta_seconds());
                 } else {
                     claw_lift.0 = ClawLiftState::Up;
                 }
             }
             ClawLiftState::Up => {
                 if height <= ClawLift::START_HEIGHT {
-                    claw_lift_position.translation.y += ClawLift::SPEED * time.delta_seconds();
+                    claw_lift_position.translation.y += ClawLift::SPEED * clock.delta_seconds();
                 } else {
                     if let Ok((mut claw_controller, transform)) = claw_controller_query.get_single_mut() {
                         claw_controller.0 = ClawControllerState::ReturnToBase(transform.translation);
@@ -211,7 +230,7 @@ fn claw_lift_system(
 }
 
 fn claw_return_system(
-    time: Res<Time>,
+    clock: Res<GameClock>,
     mut events: EventWriter<ClawReturnedToBaseEvent>,
     mut claw_controller_query: Query<(&mut ClawController, &mut Transform)>,
     glue_query: Query<(Entity, &Glue), With<ClawSensor>>,
@@ -222,17 +241,19 @@ fn claw_return_system(
             let base = Vec3::from(ClawController::BASE_POS);
             let current_diff = base - transform.translation;
             let start_diff = base - start_pos;
-            let step = start_diff / ClawController::STEP * time.delta_seconds();
+            let step = start_diff / ClawController::STEP * clock.delta_seconds();
 
             if current_diff.abs().max_element() > step.abs().max_element() * 2.0 {
                 transform.translation += step;
             } else {
                 if let Ok((entity, glue)) = glue_query.get_single() {
                     commands.entity(entity).remove::<Glue>();
-                    commands.entity(glue.0).insert(CollisionGroups::new(
-                        COL_GROUP_EJECTED_TOY,
-                        COL_GROUP_GLASS + COL_GROUP_TOY_EJECTION_SHELV
-                    ));
+                    commands.entity(glue.0)
+                        .insert(EjectedToy::default())
+                        .insert(CollisionGroups::new(
+                            COL_GROUP_EJECTED_TOY,
+                            COL_GROUP_GLASS + COL_GROUP_TOY_EJECTION_SHELV
+                        ));
                 }
 
                 transform.translation = ClawController::BASE_POS.into();
@@ -248,10 +269,55 @@ fn claw_manual_control_system(
     mut commands: Commands,
 ) {
     if let Ok((entity, claw_controller)) = claw_controller_query.get_single_mut() {
-        if let ClawControllerState::Manual = claw_controller.0 {
-            commands.entity(entity).insert(WASDMovement);
-        } else {
-            commands.entity(entity).remove::<WASDMovement>();
+        match claw_controller.0 {
+            ClawControllerState::Manual => {
+                commands.entity(entity).insert(WASDMovement).remove::<AxisMovement>();
+            }
+            ClawControllerState::MovingX { .. } => {
+                commands.entity(entity).insert(AxisMovement(MovementAxis::X)).remove::<WASDMovement>();
+            }
+            ClawControllerState::MovingZ { .. } => {
+                commands.entity(entity).insert(AxisMovement(MovementAxis::Z)).remove::<WASDMovement>();
+            }
+            _ => {
+                commands.entity(entity).remove::<WASDMovement>().remove::<AxisMovement>();
+            }
+        }
+    }
+}
+
+/// Steps of the one axis scheme: X while the input is held, then Z,
+/// then the claw is released like with the release button
+fn claw_axis_control_system(
+    clock: Res<GameClock>,
+    input: Res<ClawInput>,
+    mut events: EventWriter<ReleaseClawEvent>,
+    mut claw_controller_query: Query<&mut ClawController>,
+    mut claw_lift_query: Query<&mut ClawLift>,
+) {
+    // Input is cleared in the pause menu, which isn't letting go
+    if clock.is_paused() { return; }
+
+    if let (Ok(mut claw_controller), Ok(mut claw_lift)) = (
+        claw_controller_query.get_single_mut(), claw_lift_query.get_single_mut()
+    ) {
+        match claw_controller.0 {
+            ClawControllerState::MovingX { is_moved: false } if input.x != 0.0 => {
+                claw_controller.0 = ClawControllerState::MovingX { is_moved: true };
+            }
+            ClawControllerState::MovingX { is_moved: true } if input.x == 0.0 => {
+                claw_controller.0 = ClawControllerState::MovingZ { is_moved: false };
+            }
+            ClawControllerState::MovingZ { is_moved: false } if input.z != 0.0 => {
+                claw_controller.0 = ClawControllerState::MovingZ { is_moved: true };
+            }
+            ClawControllerState::MovingZ { is_moved: true } if input.z == 0.0 => {
+                // Lift is started here, so the event can't release the claw twice
+                claw_controller.0 = ClawControllerState::Locked;
+                claw_lift.0 = ClawLiftState::Down;
+                events.send(ReleaseClawEvent);
+            }
+            _ => {}
         }
     }
 }
@@ -289,3 +355,39 @@ fn claw_string_system(
         }
     }
 }
+
+/// Puts the claw back to the base in case the round is left in the middle of an attempt
+fn reset_claw_system(
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut claw_controller_query: Query<(Entity, &mut ClawController, &mut Transform)>,
+    mut claw_lift_query: Query<(&mut ClawLift, &mut Transform), Without<ClawController>>,
+    mut claw_string_query: Query<(&Handle<Mesh>, &mut Transform), (With<ClawString>, Without<ClawLift>, Without<ClawController>)>,
+    glue_query: Query<Entity, (With<ClawSensor>, With<Glue>)>,
+    mut commands: Commands,
+) {
+    if let Ok((entity, mut claw_controller, mut transform)) = claw_controller_query.get_single_mut() {
+        claw_controller.0 = ClawControllerState::Locked;
+        transform.translation = ClawController::BASE_POS.into();
+        commands.entity(entity)
+            .remove::<WASDMovement>()
+            .remove::<AxisMovement>();
+    }
+
+    if let Ok((mut claw_lift, mut transform)) = claw_lift_query.get_single_mut() {
+        claw_lift.0 = ClawLiftState::Off;
+        transform.translation = ClawController::BASE_POS.into();
+        transform.translation.y = ClawLift::START_HEIGHT;
+    }
+
+    if let Ok((handle_mesh, mut transform)) = claw_string_query.get_single_mut() {
+        if let Some(mesh) = meshes.get_mut(handle_mesh) {
+            *mesh = Capsule { radius: ClawString::RADIUS, depth: ClawString::DEPTH, ..default() }.into();
+        }
+
+        transform.translation.y = ClawString::START_HEIGHT;
+    }
+
+    for entity in glue_query.iter() {
+        commands.entity(entity).remove::<Glue>();
+    }
+}
@@ -28,8 +28,10 @@ use crate::{
     },
     assets::{
         gltf::{GltfCollection, GltfHandleStorage},
-        audio::{AudioCollection, GlassAudioChannel, AudioHandleStorage}
+        audio::{AudioCollection, GlassAudioChannel, AudioHandleStorage, DropAudioChannel}
     },
+    rng::GameRng,
+    settings::Settings,
     GameState
 };
 
@@ -41,6 +43,7 @@ impl Plugin for ClawMachinePlugin {
         app
             .init_resource::<GlassHitTime>()
             .add_system(glass_hit_system)
+            .add_system(drop_sfx_system)
             .add_exit_system(GameState::Loading, spawn_claw_machine_system);
     }
 }
@@ -56,6 +59,15 @@ const GLASS_SFX: [AudioCollection; 2] = [
     AudioCollection::Glass4
 ];
 
+const DROP_SFX: [AudioCollection; 6] = [
+    AudioCollection::Drop1,
+    AudioCollection::Drop2,
+    AudioCollection::Drop3,
+    AudioCollection::Drop4,
+    AudioCollection::Drop5,
+    AudioCollection::Drop6,
+];
+
 fn spawn_claw_machine_system(
     assets: Res<Assets<Gltf>>,
     asset_storage: Res<GltfHandleStorage>,
@@ -70,135 +82,155 @@ fn spawn_claw_machine_system(
                 machine.spawn_scene(gltf.named_scenes["claw_machine"].clone());
             });
 
-        {// Glass collision
-            let thickness = 0.02;
-            let [size_x, size_y, size_z] = [0.9, 1.1, 0.9];
-            let [x, y, z] = [-0.025, 2.7, -0.05];
-
-            let matrix = [
-                [thickness, size_y * 2.0, size_z, x + size_x, y, z],
-                [thickness, size_y * 2.0, size_z, x - size_x, y, z],
-                [size_x, thickness, size_z, x, y - size_y, z],
-                [size_x, thickness, size_z, x, y + size_y, z],
-                [size_x, size_y, thickness, x, y, z - size_z],
-                [size_x, size_y, thickness, x, y, z + size_z],
-            ];
-
-            for (index, coords) in matrix.iter().enumerate() {
-                commands.spawn()
-                    .insert(Collider::cuboid(coords[0], coords[1], coords[2]))
-                    .insert(CollisionGroups::new(
-                        if index == 2 {
-                            COL_GROUP_BOTTOM_GLASS
-                        } else {
-                            COL_GROUP_GLASS
-                        },
-                        COL_GROUP_ALL
-                    ))
-                    .insert(Friction::new(0.0))
-                    .insert(Transform::from_xyz(coords[3], coords[4], coords[5]))
-                    .insert(Glass);
-            }
+        spawn_claw_machine(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            Some(gltf.named_scenes["claw"].clone())
+        );
+    }
+}
 
-            // Inclined shelv for toy to eject
-            let shelv = matrix[2];
+/// Spawns physical part of the machine: glass colliders and the claw.
+/// Visual model of the claw is attached only if `claw_scene` is given
+pub fn spawn_claw_machine(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    claw_scene: Option<Handle<Scene>>,
+) {
+    {// Glass collision
+        let thickness = 0.02;
+        let [size_x, size_y, size_z] = [0.9, 1.1, 0.9];
+        let [x, y, z] = [-0.025, 2.7, -0.05];
 
-            for index in 0..2 {
-                let mut transform = Transform::from_xyz(shelv[3], shelv[4], shelv[5]);
+        let matrix = [
+            [thickness, size_y * 2.0, size_z, x + size_x, y, z],
+            [thickness, size_y * 2.0, size_z, x - size_x, y, z],
+            [size_x, thickness, size_z, x, y - size_y, z],
+            [size_x, thickness, size_z, x, y + size_y, z],
+            [size_x, size_y, thickness, x, y, z - size_z],
+            [size_x, size_y, thickness, x, y, z + size_z],
+        ];
 
-                commands.spawn()
-                    .insert(
-                        if index == 0 {
-                            transform.translation.z += 0.2;
-                            transform.with_rotation(Quat::from_rotation_x(65.0 * PI / 180.0))
-                        } else {
-                            transform.translation.y -= 0.6;
-                            transform
-                        }
-                    )
-                    .insert(Collider::cuboid(shelv[0], shelv[1], shelv[2]))
-                    .insert(CollisionGroups::new(COL_GROUP_TOY_EJECTION_SHELV, COL_GROUP_EJECTED_TOY));
-            }
+        for (index, coords) in matrix.iter().enumerate() {
+            commands.spawn()
+                .insert(Collider::cuboid(coords[0], coords[1], coords[2]))
+                .insert(CollisionGroups::new(
+                    if index == 2 {
+                        COL_GROUP_BOTTOM_GLASS
+                    } else {
+                        COL_GROUP_GLASS
+                    },
+                    COL_GROUP_ALL
+                ))
+                .insert(Friction::new(0.0))
+                .insert(Transform::from_xyz(coords[3], coords[4], coords[5]))
+                .insert(Glass);
         }
 
-        commands.spawn()
-            .insert(ClawController(ClawControllerState::Locked))
-            .insert_bundle((
-                Transform::from_translation(ClawController::BASE_POS.into()),
-                GlobalTransform::identity()
-            ))
-            .insert(Collider::cuboid(0.2, 0.1, 0.2))
-            .insert(ColliderMassProperties::Density(140.0))
-            .insert(RigidBody::Dynamic)
-            .insert(LockedAxes::TRANSLATION_LOCKED_Y | LockedAxes::ROTATION_LOCKED)
-            .insert(WASDMovement)
-            .with_children(|parent| {
-                parent.spawn()
-                    .insert(ClawString)
-                    .insert_bundle(PbrBundle {
-                        transform: Transform::from_xyz(0.001, ClawString::START_HEIGHT, -0.004),
-                        mesh: meshes.add(
-                             Capsule {
-                                radius: ClawString::RADIUS,
-                                depth: ClawString::DEPTH,
-                                ..default()
-                            }.into()
-                        ),
-                        material: materials.add(Color::BLACK.into()),
-                        ..default()
-                    });
-            });
+        // Inclined shelv for toy to eject
+        let shelv = matrix[2];
 
-        let claw_lift = commands.spawn()
-            .insert(ClawLift(ClawLiftState::Off))
-            .insert_bundle((Transform::from_xyz(0.0, ClawLift::START_HEIGHT, 0.0), GlobalTransform::identity()))
+        for index in 0..2 {
+            let mut transform = Transform::from_xyz(shelv[3], shelv[4], shelv[5]);
 
-            // not using KinematicPositionBased as it causes a bug with ClawObject remain asleep when lift moves
-            .insert(RigidBody::Dynamic)
-            .id();
+            commands.spawn()
+                .insert(
+                    if index == 0 {
+                        transform.translation.z += 0.2;
+                        transform.with_rotation(Quat::from_rotation_x(65.0 * PI / 180.0))
+                    } else {
+                        transform.translation.y -= 0.6;
+                        transform
+                    }
+                )
+                .insert(Collider::cuboid(shelv[0], shelv[1], shelv[2]))
+                .insert(CollisionGroups::new(COL_GROUP_TOY_EJECTION_SHELV, COL_GROUP_EJECTED_TOY));
+        }
+    }
 
-        let spherical_joint = SphericalJointBuilder::new()
-            .local_anchor2(Vec3::new(0.0, 0.6, 0.0));
+    commands.spawn()
+        .insert(ClawController(ClawControllerState::Locked))
+        .insert_bundle((
+            Transform::from_translation(ClawController::BASE_POS.into()),
+            GlobalTransform::identity()
+        ))
+        .insert(Collider::cuboid(0.2, 0.1, 0.2))
+        .insert(ColliderMassProperties::Density(140.0))
+        .insert(RigidBody::Dynamic)
+        .insert(LockedAxes::TRANSLATION_LOCKED_Y | LockedAxes::ROTATION_LOCKED)
+        .insert(WASDMovement)
+        .with_children(|parent| {
+            parent.spawn()
+                .insert(ClawString)
+                .insert_bundle(PbrBundle {
+                    transform: Transform::from_xyz(0.001, ClawString::START_HEIGHT, -0.004),
+                    mesh: meshes.add(
+                         Capsule {
+                            radius: ClawString::RADIUS,
+                            depth: ClawString::DEPTH,
+                            ..default()
+                        }.into()
+                    ),
+                    material: materials.add(Color::BLACK.into()),
+                    ..default()
+                });
+        });
 
-        let claw_object = commands.spawn()
-            .insert(ClawObject)
-            .insert_bundle((Transform::from_xyz(0.0, 0.0, 0.0), GlobalTransform::identity()))
-            .insert(Collider::cuboid(0.2, 0.2, 0.2))
-            .insert(Restitution::coefficient(0.7))
-            .insert(CollisionGroups::new(COL_GROUP_CLAW, COL_GROUP_GLASS))
-            .insert(ActiveEvents::COLLISION_EVENTS)
-            .insert(RigidBody::Dynamic)
-            .insert(Damping { linear_damping: 2.0, angular_damping: 2.0 })
-            .insert(ImpulseJoint::new(claw_lift, spherical_joint))
-            .insert(Velocity::default())
-            .with_children(|parent| {
+    let claw_lift = commands.spawn()
+        .insert(ClawLift(ClawLiftState::Off))
+        .insert_bundle((Transform::from_xyz(0.0, ClawLift::START_HEIGHT, 0.0), GlobalTransform::identity()))
+
+        // not using KinematicPositionBased as it causes a bug with ClawObject remain asleep when lift moves
+        .insert(RigidBody::Dynamic)
+        .id();
+
+    let spherical_joint = SphericalJointBuilder::new()
+        .local_anchor2(Vec3::new(0.0, 0.6, 0.0));
+
+    let claw_object = commands.spawn()
+        .insert(ClawObject)
+        .insert_bundle((Transform::from_xyz(0.0, 0.0, 0.0), GlobalTransform::identity()))
+        .insert(Collider::cuboid(0.2, 0.2, 0.2))
+        .insert(Restitution::coefficient(0.7))
+        .insert(CollisionGroups::new(COL_GROUP_CLAW, COL_GROUP_GLASS))
+        .insert(ActiveEvents::COLLISION_EVENTS)
+        .insert(RigidBody::Dynamic)
+        .insert(Damping { linear_damping: 2.0, angular_damping: 2.0 })
+        .insert(ImpulseJoint::new(claw_lift, spherical_joint))
+        .insert(Velocity::default())
+        .with_children(|parent| {
+            if let Some(claw_scene) = &claw_scene {
                 parent.spawn()
                     .insert_bundle((Transform::from_xyz(-0.53, -3.2, -0.50), GlobalTransform::identity()))
-                    .with_children(|claw| { claw.spawn_scene(gltf.named_scenes["claw"].clone()); });
+                    .with_children(|claw| { claw.spawn_scene(claw_scene.clone()); });
+            }
 
-                parent.spawn()
-                    .insert(ClawSensor)
-                    .insert(Collider::ball(0.1))
-                    .insert(CollisionGroups::new(COL_GROUP_ALL, COL_GROUP_ALL - COL_GROUP_CLAW - COL_GROUP_CLAW_STOPPER))
-                    .insert(ActiveEvents::COLLISION_EVENTS)
-                    .insert(Sensor(true));
-            })
-            .id();
+            parent.spawn()
+                .insert(ClawSensor)
+                .insert(Collider::ball(0.1))
+                .insert(CollisionGroups::new(COL_GROUP_ALL, COL_GROUP_ALL - COL_GROUP_CLAW - COL_GROUP_CLAW_STOPPER))
+                .insert(ActiveEvents::COLLISION_EVENTS)
+                .insert(Sensor(true));
+        })
+        .id();
 
-        commands.spawn()
-            .insert(ClawStopper)
-            .insert(Collider::cuboid(0.1, 0.05, 0.1))
-            .insert(CollisionGroups::new(COL_GROUP_CLAW_STOPPER, COL_GROUP_ALL - COL_GROUP_CLAW))
-            .insert(ActiveEvents::COLLISION_EVENTS)
-            .insert(RigidBody::Dynamic)
-            .insert(ImpulseJoint::new(claw_object, FixedJointBuilder::new().local_anchor1([0.0, 0.1, 0.0].into())))
-            .insert(ColliderDebugColor(Color::hsl(220.0, 1.0, 0.3)));
-    }
+    commands.spawn()
+        .insert(ClawStopper)
+        .insert(Collider::cuboid(0.1, 0.05, 0.1))
+        .insert(CollisionGroups::new(COL_GROUP_CLAW_STOPPER, COL_GROUP_ALL - COL_GROUP_CLAW))
+        .insert(ActiveEvents::COLLISION_EVENTS)
+        .insert(RigidBody::Dynamic)
+        .insert(ImpulseJoint::new(claw_object, FixedJointBuilder::new().local_anchor1([0.0, 0.1, 0.0].into())))
+        .insert(ColliderDebugColor(Color::hsl(220.0, 1.0, 0.3)));
 }
 
 fn glass_hit_system(
     audio: Res<AudioChannel<GlassAudioChannel>>,
     audio_storage: Res<AudioHandleStorage>,
+    mut rng: ResMut<GameRng>,
+    settings: Res<Settings>,
     time: Res<Time>,
     mut last_hit_time: ResMut<GlassHitTime>,
     mut collision_events: EventReader<CollisionEvent>,
@@ -218,8 +250,8 @@ fn glass_hit_system(
                         let hit_force = claw_velocity.linvel.abs().max_element().clamp(0.0, 1.5);
 
                         if time.seconds_since_startup() - last_hit_time.0 > 0.5 {
-                            if let Some(glass_sound) = audio_storage.get_random(&GLASS_SFX) {
-                                audio.set_volume(hit_force);
+                            if let Some(glass_sound) = audio_storage.get_random(&GLASS_SFX, rng.cosmetic()) {
+                                audio.set_volume(hit_force * settings.sfx_volume());
                                 audio.play(glass_sound.clone());
 
                                 last_hit_time.0 = time.seconds_since_startup();
@@ -230,4 +262,19 @@ fn glass_hit_system(
             }
         }
     }
-}
\ No newline at end of file
+}
+
+fn drop_sfx_system(
+    audio_drop: Res<AudioChannel<DropAudioChannel>>,
+    audio_storage: Res<AudioHandleStorage>,
+    mut rng: ResMut<GameRng>,
+    claw_lift_query: Query<&ClawLift, Changed<ClawLift>>,
+) {
+    if let Ok(claw_lift) = claw_lift_query.get_single() {
+        if let ClawLiftState::Down = claw_lift.0 {
+            if let Some(drop_sfx) = audio_storage.get_random(&DROP_SFX, rng.cosmetic()) {
+                audio_drop.play(drop_sfx.clone());
+            }
+        }
+    }
+}
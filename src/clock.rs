@@ -0,0 +1,74 @@
+use std::time::Duration;
+
+use bevy::{prelude::*, core::CoreSystem};
+use bevy_rapier3d::prelude::*;
+
+/// Drives [`GameClock`] from real time and physics from [`GameClock`]
+#[derive(Default)]
+pub struct ClockPlugin;
+
+impl Plugin for ClockPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<GameClock>()
+            .add_system_to_stage(CoreStage::First, clock_system.label(ClockSystem).after(CoreSystem::Time))
+            .add_system_to_stage(CoreStage::PreUpdate, physics_timestep_system);
+    }
+}
+
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ClockSystem;
+
+/// Time step of the gameplay simulation.
+/// Follows real frame time, but can be substituted (e.g. by a replay)
+/// in [`CoreStage::First`] after [`ClockSystem`] to reproduce a round exactly.
+/// Stands still while paused, so do gamemode timers, the claw and physics
+#[derive(Default)]
+pub struct GameClock {
+    delta: Duration,
+    is_paused: bool,
+}
+
+impl GameClock {
+    /// Physics doesn't step further than this per frame to stay stable on lags
+    const MAX_PHYSICS_STEP: f32 = 1.0 / 60.0;
+
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+
+    pub fn set_delta(&mut self, delta: Duration) {
+        self.delta = delta;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.is_paused
+    }
+
+    pub fn pause(&mut self) {
+        self.is_paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.is_paused = false;
+    }
+}
+
+fn clock_system(time: Res<Time>, mut clock: ResMut<GameClock>) {
+    let delta = if clock.is_paused() { Duration::ZERO } else { time.delta() };
+
+    clock.set_delta(delta);
+}
+
+fn physics_timestep_system(clock: Res<GameClock>, mut rapier_config: ResMut<RapierConfiguration>) {
+    rapier_config.physics_pipeline_active = !clock.is_paused();
+
+    rapier_config.timestep_mode = TimestepMode::Fixed {
+        dt: clock.delta_seconds().min(GameClock::MAX_PHYSICS_STEP),
+        substeps: 1,
+    };
+}
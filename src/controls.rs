@@ -1,45 +1,432 @@
-use bevy::{prelude::*, input::{keyboard::KeyboardInput, ElementState}, utils::HashMap};
+use std::fmt;
 
-/// Implicitly maps some scan codes to key codes to make
-/// [`Input<KeyCode>`](Input) resource work fine
-/// with any keyboard layout just like you expect it to be
+use bevy::{prelude::*, input::{keyboard::KeyboardInput, ElementState, InputSystem}, utils::HashMap};
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use strum_macros::{Display, EnumIter};
+
+use crate::{storage, clock::GameClock};
+
+/// Resolves [`GameAction`]s from keyboard, mouse and gamepad according to [`KeyMap`]
+/// and collects [`ClawInput`] from them. Letter keys are bound by scan codes,
+/// so they work fine with any keyboard layout just like you expect them to be
 #[derive(Default)]
 pub struct ControlsPlugin;
 
 impl Plugin for ControlsPlugin {
     fn build(&self, app: &mut App) {
         app
-            .init_resource::<KeyMap>()
-            .add_startup_system(register_keymap)
-            .add_system(controls_system);
+            .insert_resource(KeyMap::load())
+            .init_resource::<ScanCodes>()
+            .init_resource::<ActiveGamepad>()
+            .init_resource::<Input<GameAction>>()
+            .init_resource::<ClawInput>()
+            .add_system_to_stage(CoreStage::PreUpdate, scan_code_system.after(InputSystem))
+            .add_system_to_stage(CoreStage::PreUpdate, gamepad_connection_system.after(InputSystem))
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                action_system
+                    .label(ActionSystem)
+                    .after(scan_code_system)
+                    .after(gamepad_connection_system)
+            )
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                claw_input_system.label(ClawInputSystem).after(ActionSystem)
+            );
+    }
+}
+
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ActionSystem;
+
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ClawInputSystem;
+
+/// Everything the player can do. Gameplay and UI read [`Input<GameAction>`](Input)
+/// instead of devices, it's updated in [`CoreStage::PreUpdate`] after [`ActionSystem`]
+#[derive(Debug, Display, EnumIter, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GameAction {
+    MoveForward,
+    MoveBack,
+    MoveLeft,
+    MoveRight,
+    Drop,
+    InsertCoin,
+    MenuUp,
+    MenuDown,
+    MenuLeft,
+    MenuRight,
+    Confirm,
+    Back,
+    Pause,
+}
+
+impl GameAction {
+    /// Localization key of the action name
+    pub fn label(&self) -> &'static str {
+        match self {
+            GameAction::MoveForward => "action.move_forward",
+            GameAction::MoveBack => "action.move_back",
+            GameAction::MoveLeft => "action.move_left",
+            GameAction::MoveRight => "action.move_right",
+            GameAction::Drop => "action.drop",
+            GameAction::InsertCoin => "action.insert_coin",
+            GameAction::MenuUp => "action.menu_up",
+            GameAction::MenuDown => "action.menu_down",
+            GameAction::MenuLeft => "action.menu_left",
+            GameAction::MenuRight => "action.menu_right",
+            GameAction::Confirm => "action.confirm",
+            GameAction::Back => "action.back",
+            GameAction::Pause => "action.pause",
+        }
+    }
+
+    /// Actions of the same group are used at the same time, so they can't share bindings
+    pub fn group(&self) -> ActionGroup {
+        match self {
+            GameAction::MoveForward
+            | GameAction::MoveBack
+            | GameAction::MoveLeft
+            | GameAction::MoveRight
+            | GameAction::Drop
+            | GameAction::InsertCoin
+            | GameAction::Pause => ActionGroup::Claw,
+            GameAction::MenuUp
+            | GameAction::MenuDown
+            | GameAction::MenuLeft
+            | GameAction::MenuRight
+            | GameAction::Confirm
+            | GameAction::Back => ActionGroup::Menu,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ActionGroup {
+    Claw,
+    Menu,
+}
+
+impl ActionGroup {
+    pub fn actions(&self) -> impl Iterator<Item = GameAction> + '_ {
+        GameAction::iter().filter(move |action| action.group() == *self)
+    }
+}
+
+/// Device input an action can be bound to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Binding {
+    /// Physical key position, doesn't depend on keyboard layout
+    ScanCode(u32),
+    /// Key by its meaning in the current keyboard layout
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Gamepad(GamepadButtonType),
+}
+
+impl Binding {
+    /// Letters, digits and punctuation are bound by scan code to not depend on keyboard layout
+    pub fn from_keyboard(event: &KeyboardInput) -> Self {
+        match event.key_code {
+            Some(key_code) if !Binding::is_layout_dependent(key_code) => Binding::Key(key_code),
+            _ => Binding::ScanCode(event.scan_code),
+        }
+    }
+
+    fn is_layout_dependent(key_code: KeyCode) -> bool {
+        use KeyCode::*;
+
+        matches!(
+            key_code,
+            Key1 | Key2 | Key3 | Key4 | Key5 | Key6 | Key7 | Key8 | Key9 | Key0
+            | A | B | C | D | E | F | G | H | I | J | K | L | M
+            | N | O | P | Q | R | S | T | U | V | W | X | Y | Z
+            | Minus | Equals | LBracket | RBracket | Semicolon | Apostrophe
+            | Grave | Backslash | Comma | Period | Slash
+        )
+    }
+
+    pub fn is_gamepad(&self) -> bool {
+        matches!(self, Binding::Gamepad(_))
+    }
+
+    /// Key name on QWERTY layout for the most common scan codes
+    fn scan_code_name(scan_code: u32) -> Option<char> {
+        const ROWS: [(u32, &str); 4] = [(2, "1234567890"), (16, "QWERTYUIOP"), (30, "ASDFGHJKL"), (44, "ZXCVBNM")];
+
+        ROWS.iter().find_map(|(first, keys)| {
+            scan_code.checked_sub(*first).and_then(|index| keys.chars().nth(index as usize))
+        })
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Binding::ScanCode(scan_code) => match Binding::scan_code_name(*scan_code) {
+                Some(name) => write!(f, "{}", name),
+                None => write!(f, "Key #{}", scan_code),
+            },
+            Binding::Key(key_code) => write!(f, "{:?}", key_code),
+            Binding::Mouse(button) => write!(f, "Mouse {:?}", button),
+            Binding::Gamepad(button_type) => write!(f, "Pad {:?}", button_type),
+        }
+    }
+}
+
+/// Bindings of every [`GameAction`], stored in `bindings.ron` of the user data directory
+#[derive(Clone, Serialize, Deserialize)]
+pub struct KeyMap(pub HashMap<GameAction, Vec<Binding>>);
+
+impl KeyMap {
+    pub const FILE_NAME: &'static str = "bindings.ron";
+
+    /// Loads saved bindings, actions missing in the file get default ones
+    pub fn load() -> Self {
+        let mut key_map = storage::load::<KeyMap>(KeyMap::FILE_NAME).unwrap_or_default();
+
+        for (action, bindings) in KeyMap::default().0 {
+            key_map.0.entry(action).or_insert(bindings);
+        }
+
+        key_map
+    }
+
+    pub fn save(&self) {
+        storage::save(KeyMap::FILE_NAME, self);
+    }
+
+    pub fn bindings(&self, action: GameAction) -> &[Binding] {
+        self.0.get(&action).map_or(&[], |bindings| bindings.as_slice())
+    }
+
+    /// Action of the same group that already uses the binding
+    pub fn conflict(&self, action: GameAction, binding: Binding) -> Option<GameAction> {
+        action.group()
+            .actions()
+            .filter(|other| *other != action)
+            .find(|other| self.bindings(*other).contains(&binding))
+    }
+
+    /// Replaces the first binding of the same kind (gamepad or keyboard and mouse)
+    pub fn rebind(&mut self, action: GameAction, binding: Binding) {
+        let bindings = self.0.entry(action).or_default();
+
+        if bindings.contains(&binding) { return; }
+
+        match bindings.iter().position(|existing| existing.is_gamepad() == binding.is_gamepad()) {
+            Some(index) => bindings[index] = binding,
+            None => bindings.push(binding),
+        }
+    }
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        use Binding::*;
+
+        // Scan codes of W, A, S, D and C keys on QWERTY layout
+        const W: u32 = 17;
+        const A: u32 = 30;
+        const S: u32 = 31;
+        const D: u32 = 32;
+        const C: u32 = 46;
+
+        let bindings = GameAction::iter()
+            .map(|action| {
+                let bindings = match action {
+                    GameAction::MoveForward | GameAction::MenuUp => vec![
+                        ScanCode(W), Key(KeyCode::Up), Gamepad(GamepadButtonType::DPadUp)
+                    ],
+                    GameAction::MoveBack | GameAction::MenuDown => vec![
+                        ScanCode(S), Key(KeyCode::Down), Gamepad(GamepadButtonType::DPadDown)
+                    ],
+                    GameAction::MoveLeft | GameAction::MenuLeft => vec![
+                        ScanCode(A), Key(KeyCode::Left), Gamepad(GamepadButtonType::DPadLeft)
+                    ],
+                    GameAction::MoveRight | GameAction::MenuRight => vec![
+                        ScanCode(D), Key(KeyCode::Right), Gamepad(GamepadButtonType::DPadRight)
+                    ],
+                    GameAction::Drop | GameAction::Confirm => vec![
+                        Key(KeyCode::Return), Key(KeyCode::NumpadEnter), Gamepad(GamepadButtonType::South)
+                    ],
+                    GameAction::InsertCoin => vec![
+                        ScanCode(C), Gamepad(GamepadButtonType::Select)
+                    ],
+                    GameAction::Back => vec![
+                        Key(KeyCode::Escape), Key(KeyCode::Back), Gamepad(GamepadButtonType::East)
+                    ],
+                    GameAction::Pause => vec![
+                        Key(KeyCode::Escape), Gamepad(GamepadButtonType::Start)
+                    ],
+                };
+
+                (action, bindings)
+            })
+            .collect();
+
+        Self(bindings)
     }
 }
 
+/// Input that drives the claw, collected once per frame in [`CoreStage::PreUpdate`].
+/// Claw systems read it instead of devices so it can be recorded and played back
+#[derive(Default, Clone, Copy, Serialize, Deserialize)]
+pub struct ClawInput {
+    /// Movement along X axis in range -1.0..=1.0
+    pub x: f32,
+    /// Movement along Z axis in range -1.0..=1.0
+    pub z: f32,
+    pub release: bool,
+    /// Coin inserted in the arcade mode, missing in older replays
+    #[serde(default)]
+    pub coin: bool,
+}
+
+/// Devices don't feed [`ClawInput`] while this resource exists
+pub struct ClawInputOverride;
+
+/// Keyboard keys by scan code
+#[derive(Default)]
+pub struct ScanCodes(pub Input<u32>);
+
+/// Gamepad the game listens to. The first connected one is picked
+/// and another connected one takes over when it's unplugged
 #[derive(Default)]
-struct KeyMap(HashMap<u32, KeyCode>);
+pub struct ActiveGamepad(pub Option<Gamepad>);
+
+impl ActiveGamepad {
+    /// Stick deflection treated as a menu navigation press
+    const MENU_STICK_THRESHOLD: f32 = 0.5;
+
+    fn pressed(&self, buttons: &Input<GamepadButton>, button_type: GamepadButtonType) -> bool {
+        self.0.map_or(false, |gamepad| buttons.pressed(GamepadButton(gamepad, button_type)))
+    }
 
-fn register_keymap(mut key_map: ResMut<KeyMap>) {
-    key_map.0.insert(17, KeyCode::W);
-    key_map.0.insert(30, KeyCode::A);
-    key_map.0.insert(31, KeyCode::S);
-    key_map.0.insert(32, KeyCode::D);
+    pub fn just_pressed(&self, buttons: &Input<GamepadButton>, button_type: GamepadButtonType) -> bool {
+        self.0.map_or(false, |gamepad| buttons.just_pressed(GamepadButton(gamepad, button_type)))
+    }
+
+    fn axis(&self, axes: &Axis<GamepadAxis>, axis_type: GamepadAxisType) -> f32 {
+        self.0
+            .and_then(|gamepad| axes.get(GamepadAxis(gamepad, axis_type)))
+            .unwrap_or(0.0)
+    }
 }
 
-fn controls_system(
-    key_map: Res<KeyMap>,
-    mut keyboard_input: ResMut<Input<KeyCode>>,
+fn scan_code_system(
+    mut scan_codes: ResMut<ScanCodes>,
     mut keyboard_events: EventReader<KeyboardInput>
 ) {
+    scan_codes.0.clear();
+
     for event in keyboard_events.iter() {
         match event.state {
-            ElementState::Pressed => key_map
-                .0
-                .get(&event.scan_code)
-                .and_then(|key_code| Some(keyboard_input.press(*key_code))),
-            ElementState::Released => key_map
-                .0
-                .get(&event.scan_code)
-                .and_then(|key_code| Some(keyboard_input.release(*key_code))),
+            ElementState::Pressed => scan_codes.0.press(event.scan_code),
+            ElementState::Released => scan_codes.0.release(event.scan_code),
+        }
+    }
+}
+
+fn gamepad_connection_system(
+    gamepads: Res<Gamepads>,
+    mut events: EventReader<GamepadEvent>,
+    mut active_gamepad: ResMut<ActiveGamepad>,
+) {
+    for GamepadEvent(gamepad, event_type) in events.iter() {
+        match event_type {
+            GamepadEventType::Connected => {
+                if active_gamepad.0.is_none() {
+                    info!("{:?} is used for controls", gamepad);
+
+                    active_gamepad.0 = Some(*gamepad);
+                }
+            }
+            GamepadEventType::Disconnected => {
+                if active_gamepad.0 == Some(*gamepad) {
+                    active_gamepad.0 = gamepads.iter().find(|other| *other != gamepad).copied();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn action_system(
+    key_map: Res<KeyMap>,
+    scan_codes: Res<ScanCodes>,
+    keyboard: Res<Input<KeyCode>>,
+    mouse: Res<Input<MouseButton>>,
+    gamepad: Res<ActiveGamepad>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    mut actions: ResMut<Input<GameAction>>,
+) {
+    actions.clear();
+
+    let stick = gamepad.axis(&gamepad_axes, GamepadAxisType::LeftStickY);
+    let stick_x = gamepad.axis(&gamepad_axes, GamepadAxisType::LeftStickX);
+
+    for action in GameAction::iter() {
+        let is_pressed = key_map.bindings(action).iter().any(|binding| match binding {
+            Binding::ScanCode(scan_code) => scan_codes.0.pressed(*scan_code),
+            Binding::Key(key_code) => keyboard.pressed(*key_code),
+            Binding::Mouse(button) => mouse.pressed(*button),
+            Binding::Gamepad(button_type) => gamepad.pressed(&gamepad_buttons, *button_type),
+        }) || match action {
+            // Tilted stick acts as a held button in menus
+            GameAction::MenuUp => stick > ActiveGamepad::MENU_STICK_THRESHOLD,
+            GameAction::MenuDown => stick < -ActiveGamepad::MENU_STICK_THRESHOLD,
+            GameAction::MenuLeft => stick_x < -ActiveGamepad::MENU_STICK_THRESHOLD,
+            GameAction::MenuRight => stick_x > ActiveGamepad::MENU_STICK_THRESHOLD,
+            _ => false,
         };
+
+        if is_pressed {
+            actions.press(action);
+        } else {
+            actions.release(action);
+        }
     }
-}
\ No newline at end of file
+}
+
+fn claw_input_system(
+    clock: Res<GameClock>,
+    actions: Res<Input<GameAction>>,
+    gamepad: Res<ActiveGamepad>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    input_override: Option<Res<ClawInputOverride>>,
+    mut claw_input: ResMut<ClawInput>,
+) {
+    if input_override.is_some() { return; }
+
+    // Presses made in the pause menu must not reach the claw
+    if clock.is_paused() {
+        *claw_input = ClawInput::default();
+
+        return;
+    }
+
+    let axis = |negative: GameAction, positive: GameAction, stick: f32| {
+        let digital = actions.pressed(positive) as i8 as f32 - actions.pressed(negative) as i8 as f32;
+
+        (digital + stick).clamp(-1.0, 1.0)
+    };
+
+    *claw_input = ClawInput {
+        x: axis(
+            GameAction::MoveLeft,
+            GameAction::MoveRight,
+            gamepad.axis(&gamepad_axes, GamepadAxisType::LeftStickX),
+        ),
+        // Stick up is positive, but forward is negative Z
+        z: axis(
+            GameAction::MoveForward,
+            GameAction::MoveBack,
+            -gamepad.axis(&gamepad_axes, GamepadAxisType::LeftStickY),
+        ),
+        release: actions.just_pressed(GameAction::Drop),
+        coin: actions.just_pressed(GameAction::InsertCoin),
+    };
+}
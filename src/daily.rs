@@ -0,0 +1,498 @@
+use std::{path::Path, time::Duration};
+
+use bevy::{prelude::*, utils::HashMap};
+use iyes_loopless::prelude::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    GameState,
+    storage,
+    assets::{gamemode::ScoringRule, theme::UiTheme},
+    controls::{ClawInput, ClawInputOverride},
+    game_results::GameResults,
+    helpers::despawn_with,
+    gamemodes::{gameplay::GameSettings, definition::{GamemodeEntry, GamemodeRegistry}},
+    high_scores::Score,
+    localization::Localization,
+    replay::{Replay, ReplayFrame, RecordedReplay, PendingReplay},
+    settings::{ControlScheme, Settings},
+    ui::controls::{button::CMUIButton, in_game_text::InGameText, spawn_backing_panel},
+};
+
+/// Daily challenge: a round built from the local date, played once per day for score.
+/// A won attempt gets a short share code, its inputs are saved next to it as an attachment
+/// another copy of the game verifies by replaying it with `--verify-daily <path>`
+#[derive(Default)]
+pub struct DailyPlugin;
+
+impl Plugin for DailyPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(DailyRecords::load())
+            .insert_resource(PendingVerification::from_args())
+            .add_enter_system(GameState::MainMenu, clear_round_system)
+            .add_system(start_verification_system.run_in_state(GameState::MainMenu))
+            .add_enter_system(GameState::InGame, start_attempt_system)
+            .add_system(record_system.run_in_state(GameState::GameResults))
+            .add_system(verify_system.run_in_state(GameState::GameResults))
+            .add_exit_system(GameState::GameResults, despawn_with::<DailyVerdict>)
+            .add_exit_system(GameState::GameResults, despawn_with::<DailyCodeText>);
+    }
+}
+
+/// Round of the day. The date picks the variant and seeds the toy layout,
+/// so every copy of the game with the same gamemodes gets the same round
+pub struct DailyChallenge<'a> {
+    /// Local date in `YYYY-MM-DD` format
+    pub date: String,
+    pub seed: u64,
+    pub entry: &'a GamemodeEntry,
+}
+
+impl<'a> DailyChallenge<'a> {
+    pub fn today(registry: &'a GamemodeRegistry) -> Option<Self> {
+        DailyChallenge::for_date(&today(), registry)
+    }
+
+    /// Arcade variants are left out, their credits and tickets belong to the wallet
+    pub fn for_date(date: &str, registry: &'a GamemodeRegistry) -> Option<Self> {
+        let seed = date_seed(date);
+        let entries: Vec<&GamemodeEntry> = registry
+            .iter()
+            .filter(|entry| entry.params.scoring != ScoringRule::Tickets)
+            .collect();
+
+        if entries.is_empty() { return None; }
+
+        let entry = entries[StdRng::seed_from_u64(seed).gen_range(0..entries.len())];
+
+        Some(Self { date: date.to_string(), seed, entry })
+    }
+
+    /// Sets up the round, it's started by switching to [`GameState::InGame`]
+    pub fn start(&self, settings: &mut GameSettings, control_scheme: ControlScheme, commands: &mut Commands) {
+        settings.gamemode = self.entry.gamemode;
+        settings.control_scheme = control_scheme;
+        settings.seed = Some(self.seed);
+        settings.is_playback = false;
+        settings.variant = self.entry.id.clone();
+        settings.params = Some(self.entry.params.clone());
+
+        commands.insert_resource(DailyRound { date: self.date.clone(), is_scored: false });
+    }
+}
+
+fn today() -> String {
+    chrono::Local::now().format("%Y-%m-%d").to_string()
+}
+
+/// FNV-1a hash, stable across platforms and builds unlike `DefaultHasher`
+fn hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+fn date_seed(date: &str) -> u64 {
+    hash(date.as_bytes())
+}
+
+/// Result of the scored attempt of a day
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DailyResult {
+    pub variant: String,
+    /// Missing until the round is won
+    pub score: Option<Score>,
+    /// Share code of the won attempt, see [`DailyShare::code`]
+    #[serde(default)]
+    pub code: Option<String>,
+}
+
+/// Daily attempts by date, persisted in the user data directory
+#[derive(Serialize, Deserialize, Default)]
+pub struct DailyRecords(HashMap<String, DailyResult>);
+
+impl DailyRecords {
+    const FILE_NAME: &'static str = "daily.ron";
+
+    fn load() -> Self {
+        storage::load(DailyRecords::FILE_NAME).unwrap_or_default()
+    }
+
+    fn save(&self) {
+        storage::save(DailyRecords::FILE_NAME, self);
+    }
+
+    pub fn get(&self, date: &str) -> Option<&DailyResult> {
+        self.0.get(date)
+    }
+}
+
+/// Daily challenge round being played
+struct DailyRound {
+    date: String,
+    /// Only the first round of the day counts, restarts and later rounds are practice
+    is_scored: bool,
+}
+
+/// Result of a daily challenge with the inputs that lead to it. Players share the short
+/// [`DailyShare::code`], the whole file is attached when the result has to be verified
+#[derive(Serialize, Deserialize)]
+pub struct DailyShare {
+    pub version: u32,
+    pub date: String,
+    pub variant: String,
+    pub score: Score,
+    pub control_scheme: ControlScheme,
+    /// Replay frames packed by [`encode_frames`]
+    pub frames: String,
+}
+
+impl DailyShare {
+    pub const VERSION: u32 = 1;
+
+    pub fn file_name(date: &str) -> String {
+        format!("daily/{}.ron", date)
+    }
+
+    /// Date, variant, score and a hash of the inputs, e.g. `2026-10-18-speed_game-T12.34-1a2b3c4d`.
+    /// The same code is shown when the attached file is verified
+    pub fn code(&self) -> String {
+        let score = match self.score {
+            Score::Time(seconds) => format!("T{:.2}", seconds),
+            Score::Catches(toys) => format!("C{}", toys),
+            Score::Survived(seconds) => format!("S{:.2}", seconds),
+            Score::Tickets(tickets) => format!("K{}", tickets),
+        };
+        let inputs = format!("{}|{}|{}|{:?}|{}", self.date, self.variant, score, self.control_scheme, self.frames);
+
+        format!("{}-{}-{}-{:08x}", self.date, self.variant, score, hash(inputs.as_bytes()) as u32)
+    }
+
+    /// Replay of the round the file claims, `None` if it was made for other gamemodes
+    fn replay(&self, registry: &GamemodeRegistry) -> Option<Replay> {
+        let challenge = DailyChallenge::for_date(&self.date, registry)?;
+
+        if challenge.entry.id != self.variant {
+            warn!("Daily share file of {} is for {}, but this copy picks {}", self.date, self.variant, challenge.entry.id);
+
+            return None;
+        }
+
+        Some(Replay {
+            version: Replay::VERSION,
+            seed: challenge.seed,
+            gamemode: self.variant.clone(),
+            params: challenge.entry.params.clone(),
+            control_scheme: self.control_scheme,
+            frames: decode_frames(&self.frames)?,
+        })
+    }
+}
+
+const FLAG_RELEASE: u8 = 1;
+const FLAG_COIN: u8 = 1 << 1;
+const FLAG_MOVED: u8 = 1 << 2;
+
+/// Packs frames into a hex string. Every frame is a flag byte and the delta in nanoseconds
+/// as a varint, movement follows only when it differs from the previous frame
+fn encode_frames(frames: &[ReplayFrame]) -> String {
+    let mut bytes = Vec::new();
+    let mut last = ClawInput::default();
+
+    for frame in frames {
+        let input = frame.input;
+        let is_moved = input.x != last.x || input.z != last.z;
+        let mut flags = 0;
+
+        if input.release { flags |= FLAG_RELEASE; }
+        if input.coin { flags |= FLAG_COIN; }
+        if is_moved { flags |= FLAG_MOVED; }
+
+        bytes.push(flags);
+
+        let mut nanos = frame.delta.as_nanos() as u64;
+
+        while nanos >= 0x80 {
+            bytes.push(nanos as u8 | 0x80);
+            nanos >>= 7;
+        }
+        bytes.push(nanos as u8);
+
+        if is_moved {
+            bytes.extend(input.x.to_le_bytes());
+            bytes.extend(input.z.to_le_bytes());
+        }
+
+        last = input;
+    }
+
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn decode_frames(text: &str) -> Option<Vec<ReplayFrame>> {
+    let bytes = (0..text.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(text.get(index..index + 2)?, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+
+    let mut bytes = bytes.into_iter();
+    let mut frames = Vec::new();
+    let mut last = ClawInput::default();
+
+    while let Some(flags) = bytes.next() {
+        let mut nanos = 0u64;
+        let mut shift = 0;
+
+        loop {
+            let byte = bytes.next()?;
+
+            nanos |= ((byte & 0x7f) as u64).checked_shl(shift)?;
+            shift += 7;
+
+            if byte & 0x80 == 0 { break; }
+        }
+
+        let mut input = ClawInput {
+            release: flags & FLAG_RELEASE != 0,
+            coin: flags & FLAG_COIN != 0,
+            ..last
+        };
+
+        if flags & FLAG_MOVED != 0 {
+            let mut read_f32 = || -> Option<f32> {
+                Some(f32::from_le_bytes([bytes.next()?, bytes.next()?, bytes.next()?, bytes.next()?]))
+            };
+
+            input.x = read_f32()?;
+            input.z = read_f32()?;
+        }
+
+        frames.push(ReplayFrame { delta: Duration::from_nanos(nanos), input });
+        last = input;
+    }
+
+    Some(frames)
+}
+
+/// Share file given with `--verify-daily <path>`, waits in the main menu for the gamemodes to load
+struct PendingVerification(Option<DailyShare>);
+
+impl PendingVerification {
+    fn from_args() -> Self {
+        let path = std::env::args()
+            .skip_while(|arg| arg != "--verify-daily")
+            .nth(1);
+
+        Self(path.and_then(|path| storage::load_from(Path::new(&path))))
+    }
+}
+
+/// Score claimed by the share file being replayed
+struct DailyVerification {
+    claimed: Score,
+    code: String,
+}
+
+/// Result of the verification, shown apart from the rank [`GameResults`] may get
+#[derive(Component)]
+struct DailyVerdict;
+
+/// Share code of the scored attempt on the results screen
+#[derive(Component)]
+struct DailyCodeText;
+
+/// Main menu button of the daily challenge, disabled once today's attempt is used.
+/// The label shows today's result then, the description adds the share code
+pub fn menu_button(
+    id: impl ToString,
+    registry: &GamemodeRegistry,
+    records: &DailyRecords,
+    localization: &Localization,
+) -> CMUIButton {
+    let challenge = match DailyChallenge::today(registry) {
+        Some(challenge) => challenge,
+        None => return CMUIButton::new(id, "menu.daily").disabled(true).with_description("menu.daily.none"),
+    };
+
+    match records.get(&challenge.date) {
+        Some(DailyResult { score: Some(score), code, .. }) => CMUIButton::new(
+            id,
+            localization.format("menu.daily.done", &[("score", &score.text(localization))])
+        )
+            .disabled(true)
+            .with_description(localization.format("menu.daily.code", &[
+                ("code", &code.as_deref().unwrap_or_default()),
+                ("file", &DailyShare::file_name(&challenge.date)),
+            ])),
+        Some(_) => CMUIButton::new(id, "menu.daily.lost")
+            .disabled(true)
+            .with_description("menu.daily.tomorrow"),
+        None => CMUIButton::new(id, "menu.daily").with_description(localization.format("menu.daily.description", &[
+            ("name", &localization.get(&challenge.entry.params.label)),
+            ("rules", &challenge.entry.params.description(localization)),
+        ])),
+    }
+}
+
+/// Starts today's challenge from the main menu
+pub fn start_today(
+    registry: &GamemodeRegistry,
+    user_settings: &Settings,
+    settings: &mut GameSettings,
+    commands: &mut Commands,
+) {
+    if let Some(challenge) = DailyChallenge::today(registry) {
+        challenge.start(settings, user_settings.control_scheme, commands);
+        commands.insert_resource(NextState(GameState::InGame));
+    }
+}
+
+fn clear_round_system(mut commands: Commands) {
+    commands.remove_resource::<DailyRound>();
+}
+
+/// The attempt is used as soon as the round starts, so quitting doesn't give another one
+fn start_attempt_system(
+    round: Option<ResMut<DailyRound>>,
+    settings: Res<GameSettings>,
+    replay: Option<Res<ClawInputOverride>>,
+    mut records: ResMut<DailyRecords>,
+) {
+    if let (Some(mut round), None) = (round, replay) {
+        round.is_scored = records.get(&round.date).is_none();
+
+        if round.is_scored {
+            records.0.insert(round.date.clone(), DailyResult { variant: settings.variant.clone(), score: None, code: None });
+            records.save();
+        }
+    }
+}
+
+/// Won attempt gets the share code, its inputs are saved to the attached file
+fn record_system(
+    round: Option<ResMut<DailyRound>>,
+    recorded: Res<RecordedReplay>,
+    theme: Res<UiTheme>,
+    localization: Res<Localization>,
+    mut records: ResMut<DailyRecords>,
+    query: Query<&GameResults, Added<GameResults>>,
+    mut commands: Commands,
+) {
+    let (mut round, results) = match (round, query.get_single()) {
+        (Some(round), Ok(results)) if round.is_scored => (round, results),
+        _ => return,
+    };
+
+    round.is_scored = false;
+
+    if !results.is_win { return; }
+
+    let share = recorded.0.as_ref().map(|replay| DailyShare {
+        version: DailyShare::VERSION,
+        date: round.date.clone(),
+        variant: results.gamemode.clone(),
+        score: results.score,
+        control_scheme: replay.control_scheme,
+        frames: encode_frames(&replay.frames),
+    });
+    let code = share.as_ref().map(DailyShare::code);
+
+    if let Some(share) = &share {
+        storage::save(&DailyShare::file_name(&round.date), share);
+    }
+
+    if let Some(result) = records.0.get_mut(&round.date) {
+        result.score = Some(results.score);
+        result.code = code.clone();
+        records.save();
+    }
+
+    if let Some(code) = code {
+        spawn_code_text(&mut commands, &theme, localization.format("daily.code", &[("code", &code)]));
+    }
+}
+
+fn spawn_code_text(commands: &mut Commands, theme: &UiTheme, value: String) {
+    commands.spawn()
+        .insert(DailyCodeText)
+        .insert_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect { bottom: Val::Percent(5.0), left: Val::Percent(5.0), ..default() },
+                padding: Rect::all(theme.px(10.0)),
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .with_children(|parent| {
+            spawn_backing_panel(parent, theme);
+
+            parent.spawn_bundle(TextBundle {
+                text: Text::with_section(
+                    value,
+                    TextStyle {
+                        font: theme.font.clone(),
+                        font_size: theme.text_font_size,
+                        color: theme.text_color,
+                    },
+                    default()
+                ),
+                ..default()
+            });
+        });
+}
+
+fn start_verification_system(
+    registry: Res<GamemodeRegistry>,
+    mut verification: ResMut<PendingVerification>,
+    mut pending_replay: ResMut<PendingReplay>,
+    mut commands: Commands,
+) {
+    if registry.iter().next().is_none() { return; }
+
+    if let Some(share) = verification.0.take() {
+        if share.version != DailyShare::VERSION {
+            warn!("Daily share file of {} has unsupported version {}", share.date, share.version);
+
+            return;
+        }
+
+        match share.replay(&registry) {
+            Some(replay) => {
+                pending_replay.0 = Some(replay);
+                commands.insert_resource(DailyVerification { claimed: share.score, code: share.code() });
+            }
+            None => warn!("Daily share file of {} can't be replayed", share.date),
+        }
+    }
+}
+
+/// Shows the verdict and the code of the file, so it can be compared with the one the player shared
+fn verify_system(
+    verification: Option<Res<DailyVerification>>,
+    theme: Res<UiTheme>,
+    localization: Res<Localization>,
+    query: Query<&GameResults, Added<GameResults>>,
+    mut commands: Commands,
+) {
+    if let (Some(verification), Ok(results)) = (verification, query.get_single()) {
+        // Physics isn't cross-platform deterministic, so the score only matches exactly
+        // when the file is replayed by the same build on the same platform
+        let is_verified = results.is_win && results.score == verification.claimed;
+
+        if is_verified {
+            info!("Daily result verified: {:?}", verification.claimed);
+        } else {
+            warn!("Daily result claims {:?}, replay ended with {:?}", verification.claimed, results.score);
+        }
+
+        commands.spawn()
+            .insert(DailyVerdict)
+            .insert(InGameText(localization.get(
+                if is_verified { "daily.verified" } else { "daily.mismatch" }
+            )));
+        spawn_code_text(&mut commands, &theme, localization.format("daily.code", &[("code", &verification.code)]));
+        commands.remove_resource::<DailyVerification>();
+    }
+}
@@ -4,10 +4,11 @@ use iyes_loopless::prelude::*;
 use strum_macros::Display;
 
 use crate::{
-    gamemodes::{speed_game::SpeedGameProgress, number_game::NumberGameProgress},
     GameState,
+    high_scores::Score,
     assets::audio::{BackgroundAudioChannel, AudioHandleStorage, AudioCollection},
     helpers::despawn_with,
+    rng::GameRng,
     ui::controls::{menu::CMUIMenu, button::CMUIButton, ButtonPressEvent}
 };
 
@@ -29,10 +30,17 @@ impl Plugin for GameResultsPlugin {
     }
 }
 
+/// Spawned by the finished gamemode, see
+/// [`GamemodeDefinition`](crate::gamemodes::definition::GamemodeDefinition)
 #[derive(Component)]
-pub enum GameResults {
-    SpeedGame(SpeedGameProgress),
-    NumberGame(NumberGameProgress)
+pub struct GameResults {
+    /// Id of the played gamemode variant
+    pub gamemode: String,
+    pub is_win: bool,
+    pub summary: String,
+    pub score: Score,
+    /// Result of a replay playback, it's not recorded
+    pub is_playback: bool,
 }
 
 const DEFEAT_SFX: [AudioCollection; 3] = [
@@ -50,43 +58,26 @@ fn setup_system(
     query: Query<&GameResults, Added<GameResults>>,
     audio: Res<AudioChannel<BackgroundAudioChannel>>,
     audio_storage: Res<AudioHandleStorage>,
+    mut rng: ResMut<GameRng>,
     mut commands: Commands,
 ) {
     if let Ok(results) = query.get_single() {
-        let is_win: bool;
-        let win_text: String;
-
-        match results {
-            GameResults::SpeedGame(progress) => {
-                is_win = progress.toy_caught;
-                win_text = format!("{:.2} sec!", &progress.timer.elapsed_secs());
-            }
-            GameResults::NumberGame(progress) => {
-                is_win = progress.toys_caught > 0;
-                win_text = format!(
-                    "{} toy{}!",
-                    &progress.toys_caught,
-                    if progress.toys_caught != 1 { "s" } else { "" }
-                );
-            }
-        }
-
-        if is_win {
+        if results.is_win {
             audio_storage.0.get(&AudioCollection::Win1)
         } else {
-            audio_storage.get_random(&DEFEAT_SFX)
+            audio_storage.get_random(&DEFEAT_SFX, rng.cosmetic())
         }.and_then(|sfx| Some(audio.play(sfx.clone())));
 
-        commands.spawn().insert(CMUIMenu {
-            title: if is_win {
-                win_text
+        commands.spawn().insert(CMUIMenu::new(
+            if results.is_win {
+                results.summary.clone()
             } else {
-                "You lose =(".to_string()
+                "results.lose".to_string()
             },
-            buttons: vec![
-                CMUIButton::new(ResultButtons::MainMenu.to_string(), "Main menu").selected(),
+            vec![
+                CMUIButton::new(ResultButtons::MainMenu.to_string(), "results.main_menu").selected(),
             ]
-        });
+        ));
     }
 }
 
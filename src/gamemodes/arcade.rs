@@ -0,0 +1,286 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+use rand::Rng;
+
+use crate::{
+    GameState,
+    arcade::ArcadeWallet,
+    assets::{gamemode::{GamemodeAsset, ScoringRule}, theme::UiTheme},
+    claw::{ClawController, ClawControllerState, ClawLift, ClawLiftState, ToyCatchEvent},
+    controls::{ClawInput, ClawInputOverride, GameAction, KeyMap},
+    helpers::despawn_with,
+    high_scores::Score,
+    localization::Localization,
+    replay::StartRoundSystem,
+    rng::GameRng,
+    toy::{Toy, ToyValue},
+    ui::controls::spawn_backing_panel,
+};
+
+use super::{gameplay::{Gamemode, GameSettings}, definition::{GamemodeDefinition, GamemodeApp, GamemodeRegistry}};
+
+/// Claw machine that takes coins. Credits come from [`ArcadeWallet`],
+/// which isn't touched while a replay is played
+#[derive(Default)]
+pub struct ArcadeGamePlugin;
+
+impl Plugin for ArcadeGamePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_gamemode::<ArcadeProgress>()
+            .add_enter_system(GameState::InGame, refill_credits_system.before(StartRoundSystem))
+            .add_enter_system(ArcadeProgress::GAMEMODE, toy_value_system)
+            .add_enter_system(ArcadeProgress::GAMEMODE, setup_hud_system)
+            .add_system_set(
+                ConditionSet::new()
+                    .run_in_state(ArcadeProgress::GAMEMODE)
+                    .with_system(insert_coin_system)
+                    .with_system(lock_claw_system)
+                    .with_system(ticket_system)
+                    .with_system(hud_system)
+                    .into()
+            )
+            .add_exit_system(ArcadeProgress::GAMEMODE, despawn_with::<ArcadeHud>);
+    }
+}
+
+#[derive(Component)]
+struct ArcadeHud;
+
+#[derive(Component)]
+struct ArcadeHudText;
+
+#[derive(Component, Clone)]
+pub struct ArcadeProgress {
+    /// Time of an attempt, runs once a coin is inserted
+    timer: Timer,
+    /// Credits left to insert
+    credits: u32,
+    /// Coin is inserted and the attempt hasn't been used yet
+    coin_inserted: bool,
+    pub toys_caught: u8,
+    pub tickets: u32,
+    win_threshold: u8,
+}
+
+impl GamemodeDefinition for ArcadeProgress {
+    const GAMEMODE: Gamemode = Gamemode("Arcade");
+    const SCORING: ScoringRule = ScoringRule::Tickets;
+
+    fn setup(params: &GamemodeAsset) -> Self {
+        Self {
+            timer: Timer::from_seconds(params.time_limit, false),
+            credits: params.credits,
+            coin_inserted: false,
+            toys_caught: 0,
+            tickets: 0,
+            win_threshold: params.win_threshold,
+        }
+    }
+
+    fn update(&mut self, delta: Duration) -> bool {
+        self.coin_inserted && self.timer.tick(delta).just_finished()
+    }
+
+    /// The inserted coin is spent by the attempt
+    fn on_claw_release(&mut self) {
+        self.coin_inserted = false;
+    }
+
+    fn on_toy_catch(&mut self, _toy: Entity) {
+        self.toys_caught += 1;
+    }
+
+    fn is_over(&self) -> bool {
+        !self.coin_inserted && self.credits == 0
+    }
+
+    fn time_left(&self) -> f32 {
+        self.timer.duration().as_secs_f32() - self.timer.elapsed_secs()
+    }
+
+    fn hud_text(&self) -> String {
+        if !self.coin_inserted { return String::new(); }
+
+        format!("{:.0}", self.time_left().floor())
+    }
+
+    fn is_win(&self) -> bool {
+        self.toys_caught >= self.win_threshold
+    }
+
+    fn summary(&self, localization: &Localization) -> String {
+        localization.plural("results.tickets", self.tickets as u64, &[])
+    }
+
+    fn score(&self) -> Score {
+        Score::Tickets(self.tickets)
+    }
+}
+
+/// Empty wallet gets the credits of the variant, so the player is never stuck without them.
+/// The round starts with the credits left in the wallet, they are put to the round parameters
+/// before the replay is recorded, so a replay starts with the same ones
+fn refill_credits_system(
+    registry: Res<GamemodeRegistry>,
+    replay: Option<Res<ClawInputOverride>>,
+    mut settings: ResMut<GameSettings>,
+    mut wallet: ResMut<ArcadeWallet>,
+) {
+    if replay.is_some() { return; }
+
+    let refill = registry.get(&settings.variant).map(|entry| entry.params.credits);
+
+    if let (Some(params), Some(refill)) = (&mut settings.params, refill) {
+        if params.scoring != ScoringRule::Tickets { return; }
+
+        if wallet.credits == 0 {
+            wallet.credits = refill;
+            wallet.save();
+        }
+
+        params.credits = wallet.credits;
+    }
+}
+
+/// Toys get their values from the gameplay stream after the layout, so replays get the same ones
+fn toy_value_system(
+    settings: Res<GameSettings>,
+    mut rng: ResMut<GameRng>,
+    toys: Query<Entity, With<Toy>>,
+    mut commands: Commands,
+) {
+    let values = settings.params.as_ref().map_or(&[][..], |params| params.ticket_values.as_slice());
+
+    for toy in toys.iter() {
+        let value = if values.is_empty() { 1 } else { values[rng.gameplay().gen_range(0..values.len())] };
+
+        commands.entity(toy).insert(ToyValue(value));
+    }
+}
+
+/// Takes a credit and gives the claw to the player for one attempt
+fn insert_coin_system(
+    settings: Res<GameSettings>,
+    input: Res<ClawInput>,
+    replay: Option<Res<ClawInputOverride>>,
+    mut wallet: ResMut<ArcadeWallet>,
+    mut progress_query: Query<&mut ArcadeProgress>,
+    mut claw_query: Query<&mut ClawController>,
+    lift_query: Query<&ClawLift>,
+) {
+    if let (Ok(mut progress), Ok(mut claw_controller), Ok(claw_lift)) = (
+        progress_query.get_single_mut(),
+        claw_query.get_single_mut(),
+        lift_query.get_single(),
+    ) {
+        let is_claw_idle = claw_controller.0 == ClawControllerState::Locked && matches!(claw_lift.0, ClawLiftState::Off);
+
+        if !input.coin || progress.coin_inserted || progress.credits == 0 || !is_claw_idle { return; }
+
+        progress.credits -= 1;
+        progress.coin_inserted = true;
+        progress.timer.reset();
+        claw_controller.0 = ClawControllerState::controlled(settings.control_scheme);
+
+        if replay.is_none() {
+            wallet.credits = progress.credits;
+            wallet.save();
+        }
+    }
+}
+
+/// Gameplay unlocks the claw after the countdown and every attempt, it waits for a coin here
+fn lock_claw_system(
+    progress_query: Query<&ArcadeProgress>,
+    mut claw_query: Query<&mut ClawController>,
+) {
+    if let (Ok(progress), Ok(mut claw_controller)) = (progress_query.get_single(), claw_query.get_single_mut()) {
+        if claw_controller.0.is_controlled() && !progress.coin_inserted {
+            claw_controller.0 = ClawControllerState::Locked;
+        }
+    }
+}
+
+fn ticket_system(
+    replay: Option<Res<ClawInputOverride>>,
+    mut events: EventReader<ToyCatchEvent>,
+    mut wallet: ResMut<ArcadeWallet>,
+    mut progress_query: Query<&mut ArcadeProgress>,
+    toys: Query<&ToyValue>,
+) {
+    if let Ok(mut progress) = progress_query.get_single_mut() {
+        for event in events.iter() {
+            let value = toys.get(event.0).map_or(0, |value| value.0);
+
+            progress.tickets += value;
+
+            if replay.is_none() {
+                wallet.tickets += value;
+                wallet.save();
+            }
+        }
+    }
+}
+
+fn setup_hud_system(theme: Res<UiTheme>, mut commands: Commands) {
+    commands.spawn()
+        .insert(ArcadeHud)
+        .insert_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect { top: Val::Percent(5.0), right: Val::Percent(5.0), ..default() },
+                padding: Rect::all(theme.px(10.0)),
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .with_children(|parent| {
+            spawn_backing_panel(parent, &theme);
+
+            parent.spawn()
+                .insert(ArcadeHudText)
+                .insert_bundle(TextBundle {
+                    text: Text::with_section(
+                        "",
+                        TextStyle {
+                            font: theme.font.clone(),
+                            font_size: theme.text_font_size,
+                            color: theme.text_color,
+                        },
+                        default()
+                    ),
+                    ..default()
+                });
+        });
+}
+
+fn hud_system(
+    localization: Res<Localization>,
+    key_map: Res<KeyMap>,
+    progress_query: Query<&ArcadeProgress>,
+    mut texts: Query<&mut Text, With<ArcadeHudText>>,
+) {
+    if let (Ok(progress), Ok(mut text)) = (progress_query.get_single(), texts.get_single_mut()) {
+        let mut value = localization.format("arcade.balance", &[
+            ("credits", &progress.credits),
+            ("tickets", &progress.tickets),
+        ]);
+
+        if !progress.coin_inserted && progress.credits > 0 {
+            let key = key_map
+                .bindings(GameAction::InsertCoin)
+                .first()
+                .map(|binding| binding.to_string())
+                .unwrap_or_default();
+
+            value.push('\n');
+            value.push_str(&localization.format("arcade.insert_coin", &[("key", &key)]));
+        }
+
+        text.sections[0].value = value;
+    }
+}
@@ -0,0 +1,266 @@
+use std::time::Duration;
+
+use bevy::{prelude::*, utils::HashMap};
+use bevy_kira_audio::AudioChannel;
+use iyes_loopless::prelude::*;
+
+use crate::{
+    GameState,
+    clock::GameClock,
+    claw::{ClawController, ClawControllerState, ClawReturnedToBaseEvent, ReleaseClawEvent, ToyCatchEvent},
+    game_results::GameResults,
+    high_scores::Score,
+    settings::Settings,
+    localization::Localization,
+    ui::controls::in_game_text::InGameText,
+    assets::{
+        audio::{stop_background_audio_system, BackgroundAudioChannel, AudioHandleStorage, AudioCollection},
+        gamemode::{GamemodeAsset, ScoringRule, GamemodeHandleStorage}
+    },
+};
+
+use super::gameplay::{Gamemode, GameSettings};
+
+/// Describes a gamemode. Implemented by the component
+/// that holds progress of the current round.
+/// Variants of the gamemode are loaded from [`GamemodeAsset`]s with matching [`ScoringRule`]
+pub trait GamemodeDefinition: Component + Clone {
+    /// State in which the gamemode is running, also serves as its name
+    const GAMEMODE: Gamemode;
+    const SCORING: ScoringRule;
+
+    /// Creates progress for a new round
+    fn setup(params: &GamemodeAsset) -> Self;
+
+    /// Called every frame of the round.
+    /// Returning `true` forces the claw to be released, e.g. when time is over
+    fn update(&mut self, delta: Duration) -> bool;
+
+    fn on_claw_release(&mut self) {}
+
+    /// Called with the entity of the toy grabbed by the claw
+    fn on_toy_catch(&mut self, toy: Entity);
+
+    /// Checked when claw has returned to its base.
+    /// If the round is not over the player gets control back
+    fn is_over(&self) -> bool;
+
+    /// Seconds left until the end of the round
+    fn time_left(&self) -> f32;
+
+    fn hud_text(&self) -> String;
+
+    fn is_win(&self) -> bool;
+
+    /// Text shown on the results screen in case of win
+    fn summary(&self, localization: &Localization) -> String;
+
+    /// Result compared in the high score table
+    fn score(&self) -> Score;
+}
+
+/// Gamemode variant available to play
+pub struct GamemodeEntry {
+    /// File name of the variant without extensions
+    pub id: String,
+    pub gamemode: Gamemode,
+    pub params: GamemodeAsset,
+}
+
+/// Gamemodes registered with [`GamemodeApp::add_gamemode`]
+/// and their variants loaded from `assets/gamemodes`
+#[derive(Default)]
+pub struct GamemodeRegistry {
+    rules: HashMap<ScoringRule, Gamemode>,
+    entries: Vec<GamemodeEntry>,
+}
+
+impl GamemodeRegistry {
+    pub fn iter(&self) -> impl Iterator<Item = &GamemodeEntry> {
+        self.entries.iter()
+    }
+
+    pub fn get(&self, id: &str) -> Option<&GamemodeEntry> {
+        self.entries.iter().find(|entry| entry.id == id)
+    }
+
+    /// Gamemode that implements the scoring rule
+    pub fn gamemode(&self, scoring: ScoringRule) -> Option<Gamemode> {
+        self.rules.get(&scoring).copied()
+    }
+}
+
+pub trait GamemodeApp {
+    fn add_gamemode<T: GamemodeDefinition>(&mut self) -> &mut Self;
+}
+
+impl GamemodeApp for App {
+    fn add_gamemode<T: GamemodeDefinition>(&mut self) -> &mut Self {
+        self.world
+            .get_resource_or_insert_with(GamemodeRegistry::default)
+            .rules
+            .insert(T::SCORING, T::GAMEMODE);
+
+        self
+            .add_enter_system(T::GAMEMODE, setup_system::<T>)
+            .add_system_set(
+                ConditionSet::new()
+                    .run_in_state(T::GAMEMODE)
+                    .with_system(update_system::<T>)
+                    .with_system(heartbeat_system::<T>)
+                    .with_system(claw_release_system::<T>)
+                    .with_system(toy_catch_system::<T>)
+                    .with_system(claw_return_system::<T>.run_on_event::<ClawReturnedToBaseEvent>())
+                    .into()
+            )
+            .add_exit_system(T::GAMEMODE, stop_background_audio_system)
+            .add_exit_system(T::GAMEMODE, exit_system::<T>)
+    }
+}
+
+#[derive(Component)]
+struct HeartbeatPlayed(bool);
+
+/// Marks the entity holding progress of the running round
+#[derive(Component)]
+pub struct RoundProgress;
+
+/// Fills the registry with variants of registered gamemodes
+/// every time gamemode assets are loaded or changed
+pub fn sync_registry_system(
+    mut events: EventReader<AssetEvent<GamemodeAsset>>,
+    asset_server: Res<AssetServer>,
+    assets: Res<Assets<GamemodeAsset>>,
+    asset_storage: Res<GamemodeHandleStorage>,
+    mut registry: ResMut<GamemodeRegistry>,
+) {
+    if events.iter().count() == 0 { return; }
+
+    let mut entries: Vec<GamemodeEntry> = asset_storage.0
+        .iter()
+        .filter_map(|handle| {
+            let params = assets.get(handle)?;
+            let gamemode = *registry.rules.get(&params.scoring)?;
+            let path = asset_server.get_handle_path(handle)?;
+            let file_name = path.path().file_name()?.to_str()?;
+
+            Some(GamemodeEntry {
+                id: file_name.split('.').next()?.to_string(),
+                gamemode,
+                params: params.clone(),
+            })
+        })
+        .collect();
+
+    entries.sort_by(|a, b| a.id.cmp(&b.id));
+    registry.entries = entries;
+}
+
+fn setup_system<T: GamemodeDefinition>(settings: Res<GameSettings>, mut commands: Commands) {
+    if let Some(params) = &settings.params {
+        commands.spawn()
+            .insert(T::setup(params))
+            .insert(RoundProgress)
+            .insert(HeartbeatPlayed(false))
+            .insert(InGameText(String::new()));
+    }
+}
+
+fn update_system<T: GamemodeDefinition>(
+    clock: Res<GameClock>,
+    mut progress_query: Query<&mut T>,
+    mut text_query: Query<&mut Text, With<InGameText>>,
+    mut events: EventWriter<ReleaseClawEvent>,
+) {
+    if let Ok(mut progress) = progress_query.get_single_mut() {
+        if progress.update(clock.delta()) {
+            events.send(ReleaseClawEvent);
+        }
+
+        for mut text in text_query.iter_mut() {
+            text.sections[0].value = progress.hud_text();
+        }
+    }
+}
+
+fn heartbeat_system<T: GamemodeDefinition>(
+    settings: Res<GameSettings>,
+    user_settings: Res<Settings>,
+    audio_background: Res<AudioChannel<BackgroundAudioChannel>>,
+    audio_storage: Res<AudioHandleStorage>,
+    mut progress_query: Query<(&T, &mut HeartbeatPlayed)>,
+) {
+    let warning = settings.params.as_ref().and_then(|params| params.heartbeat_warning);
+
+    if let (Ok((progress, mut heartbeat_played)), Some(warning)) = (progress_query.get_single_mut(), warning) {
+        if let Some(heartbeat) = audio_storage.0.get(&AudioCollection::Heartbeat) {
+            if progress.time_left() <= warning && !heartbeat_played.0 {
+                audio_background.set_volume(user_settings.music_volume() * BackgroundAudioChannel::HEARTBEAT_GAIN);
+                audio_background.play(heartbeat.clone());
+                heartbeat_played.0 = true
+            } else if progress.time_left() > warning && heartbeat_played.0 {
+                // Time bonus moved the end away, warn again when it comes close
+                audio_background.set_volume(user_settings.music_volume());
+                heartbeat_played.0 = false
+            }
+        }
+    }
+}
+
+fn claw_release_system<T: GamemodeDefinition>(
+    mut events: EventReader<ReleaseClawEvent>,
+    mut query: Query<&mut T>,
+) {
+    if let Ok(mut progress) = query.get_single_mut() {
+        for _ in events.iter() {
+            progress.on_claw_release();
+        }
+    }
+}
+
+fn toy_catch_system<T: GamemodeDefinition>(
+    mut events: EventReader<ToyCatchEvent>,
+    mut query: Query<&mut T>,
+) {
+    if let Ok(mut progress) = query.get_single_mut() {
+        for event in events.iter() {
+            progress.on_toy_catch(event.0);
+        }
+    }
+}
+
+fn claw_return_system<T: GamemodeDefinition>(
+    settings: Res<GameSettings>,
+    mut claw_controller_query: Query<&mut ClawController>,
+    progress_query: Query<&T>,
+    mut commands: Commands
+) {
+    if let (Ok(mut claw_controller), Ok(progress)) = (
+        claw_controller_query.get_single_mut(),
+        progress_query.get_single()
+    ) {
+        if progress.is_over() {
+            commands.insert_resource(NextState(GameState::GameResults));
+        } else {
+            claw_controller.0 = ClawControllerState::controlled(settings.control_scheme);
+        }
+    }
+}
+
+fn exit_system<T: GamemodeDefinition>(
+    settings: Res<GameSettings>,
+    localization: Res<Localization>,
+    query: Query<(Entity, &T)>,
+    mut commands: Commands
+) {
+    if let Ok((entity, progress)) = query.get_single() {
+        commands.spawn().insert(GameResults {
+            gamemode: settings.variant.clone(),
+            is_win: progress.is_win(),
+            summary: progress.summary(&localization),
+            score: progress.score(),
+            is_playback: settings.is_playback,
+        });
+        commands.entity(entity).despawn_recursive();
+    }
+}
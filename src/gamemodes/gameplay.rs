@@ -4,10 +4,23 @@ use iyes_loopless::prelude::*;
 
 use crate::{
     claw::{ClawController, ClawControllerState},
-    GameState, assets::audio::{UiAudioChannel, AudioHandleStorage, AudioCollection, BackgroundAudioChannel},
-    ui::controls::Controls, constants::PURPLE_COLOR, helpers::despawn_with, toy::RespawnToysEvent,
+    GameState,
+    clock::GameClock,
+    assets::{
+        audio::{UiAudioChannel, AudioHandleStorage, AudioCollection, BackgroundAudioChannel},
+        gamemode::GamemodeAsset,
+        theme::UiTheme,
+    },
+    ui::controls::spawn_backing_panel,
+    helpers::despawn_with, toy::RespawnToysEvent,
+    rng::GameRng,
+    settings::ControlScheme,
+    localization::Localization,
+    controls::{GameAction, KeyMap},
 };
 
+use super::definition::{GamemodeRegistry, sync_registry_system};
+
 #[derive(Default)]
 pub struct GameplayPlugin;
 
@@ -15,51 +28,65 @@ impl Plugin for GameplayPlugin {
     fn build(&self, app: &mut App) {
         app
             .init_resource::<GameSettings>()
-            .add_loopless_state(Gamemode::None)
+            .init_resource::<GamemodeRegistry>()
+            .add_loopless_state(Gamemode::NONE)
+            .add_system(sync_registry_system)
             .add_enter_system(GameState::InGame, setup_system)
+            .add_enter_system(GameState::InGame, setup_axis_hint_system)
             .add_system_set(
                 ConditionSet::new()
                     .run_in_state(GameState::InGame)
                     .with_system(countdown_system)
+                    .with_system(axis_hint_system)
                     .into()
             )
             .add_exit_system(GameState::InGame, despawn_with::<Countdown>)
+            .add_exit_system(GameState::InGame, despawn_with::<AxisHint>)
             .add_exit_system(GameState::InGame, exit_system);
     }
 }
 
+/// Name of the active gamemode. Used as a loopless state
+/// so every gamemode runs its systems in its own state
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum Gamemode {
-    None,
-    SpeedGame,
-    NumberGame
+pub struct Gamemode(pub &'static str);
+
+impl Gamemode {
+    pub const NONE: Gamemode = Gamemode("None");
 }
 
 pub struct GameSettings {
-    pub gamemode: Gamemode
+    pub gamemode: Gamemode,
+    /// Taken from the settings when the round is started, so a replay keeps its own
+    pub control_scheme: ControlScheme,
+    /// Id of the selected gamemode variant
+    pub variant: String,
+    /// Parameters of the selected gamemode variant
+    pub params: Option<GamemodeAsset>,
+    /// Round seed of the [`GameRng`], drawn from the session if not set
+    pub seed: Option<u64>,
+    /// Round is a replay playback, its result doesn't count
+    pub is_playback: bool,
 }
 
 impl Default for GameSettings {
     fn default() -> Self {
-        Self { gamemode: Gamemode::None }
+        Self { gamemode: Gamemode::NONE, control_scheme: default(), variant: String::new(), params: None, seed: None, is_playback: false }
     }
 }
 
 #[derive(Component)]
 struct Countdown(Timer);
+/// Tells which axis the claw moves along with [`ControlScheme::OneAxis`]
+#[derive(Component)]
+struct AxisHint;
 #[derive(Component)]
 struct CountdownText;
 
-const GAMEPLAY_MUSIC: [AudioCollection; 3] = [
-    AudioCollection::Gameplay1,
-    AudioCollection::Gameplay2,
-    AudioCollection::Gameplay3
-];
-
 fn setup_system(
     audio: Res<AudioChannel<UiAudioChannel>>,
     audio_storage: Res<AudioHandleStorage>,
-    controls: Res<Controls>,
+    theme: Res<UiTheme>,
     mut events: EventWriter<RespawnToysEvent>,
     mut commands: Commands,
 ) {
@@ -78,15 +105,17 @@ fn setup_system(
                 ..default()
             })
             .with_children(|parent| {
+                spawn_backing_panel(parent, &theme);
+
                 for index in 0..=1 {
                     parent.spawn()
                         .insert(CountdownText)
                         .insert_bundle(TextBundle {
                             style: Style {
-                                position_type: PositionType::Absolute,
+                                position_type: if index == 0 { PositionType::Relative } else { PositionType::Absolute },
                                 position: Rect {
-                                    top: Val::Px((-index * 4) as f32),
-                                    left: Val::Px((-index * 4) as f32),
+                                    top: theme.px((-index * 4) as f32),
+                                    left: theme.px((-index * 4) as f32),
                                     ..default()
                                 },
                                 ..default()
@@ -94,13 +123,9 @@ fn setup_system(
                             text: Text::with_section(
                                 secs.to_string(),
                                 TextStyle {
-                                    font: controls.header_font.clone(),
-                                    font_size: 200.0,
-                                    color: if index == 0 {
-                                        PURPLE_COLOR
-                                    } else {
-                                        Color::ANTIQUE_WHITE
-                                    },
+                                    font: theme.header_font.clone(),
+                                    font_size: theme.countdown_font_size,
+                                    color: theme.layer_color(index),
                                 },
                                 default()
                             ),
@@ -114,23 +139,26 @@ fn setup_system(
 }
 
 fn countdown_system(
-    time: Res<Time>,
+    clock: Res<GameClock>,
     audio: Res<AudioChannel<BackgroundAudioChannel>>,
     audio_storage: Res<AudioHandleStorage>,
     settings: Res<GameSettings>,
+    mut rng: ResMut<GameRng>,
     mut query_countdown: Query<(Entity, &mut Countdown)>,
     mut query_text: Query<&mut Text, With<CountdownText>>,
     mut query_claw: Query<&mut ClawController>,
     mut commands: Commands,
 ) {
     if let Ok((entity, mut countdown)) = query_countdown.get_single_mut() {
-        if countdown.0.tick(time.delta()).just_finished() {
+        if countdown.0.tick(clock.delta()).just_finished() {
             if let Ok(mut claw_controller) = query_claw.get_single_mut() {
-                if let Some(music) = audio_storage.get_random(&GAMEPLAY_MUSIC) {
+                let music = settings.params.as_ref().and_then(|params| audio_storage.get_random(&params.music, rng.cosmetic()));
+
+                if let Some(music) = music {
                     audio.play_looped(music.clone());
                 }
 
-                claw_controller.0 = ClawControllerState::Manual;
+                claw_controller.0 = ClawControllerState::controlled(settings.control_scheme);
 
                 commands.insert_resource(NextState(settings.gamemode));
                 commands.entity(entity).despawn_recursive();
@@ -143,8 +171,57 @@ fn countdown_system(
     }
 }
 
+fn setup_axis_hint_system(settings: Res<GameSettings>, theme: Res<UiTheme>, mut commands: Commands) {
+    if settings.control_scheme != ControlScheme::OneAxis { return; }
+
+    commands.spawn()
+        .insert(AxisHint)
+        .insert_bundle(TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect { bottom: Val::Percent(5.0), left: Val::Percent(5.0), ..default() },
+                ..default()
+            },
+            text: Text::with_section(
+                "",
+                TextStyle {
+                    font: theme.font.clone(),
+                    font_size: theme.text_font_size,
+                    color: theme.text_color,
+                },
+                default()
+            ),
+            ..default()
+        });
+}
+
+fn axis_hint_system(
+    localization: Res<Localization>,
+    key_map: Res<KeyMap>,
+    claw_query: Query<&ClawController>,
+    mut hint_query: Query<&mut Text, With<AxisHint>>,
+) {
+    if let (Ok(claw_controller), Ok(mut text)) = (claw_query.get_single(), hint_query.get_single_mut()) {
+        let key = |action: GameAction| -> String {
+            key_map.bindings(action).first().map(|binding| binding.to_string()).unwrap_or_default()
+        };
+
+        text.sections[0].value = match claw_controller.0 {
+            ClawControllerState::MovingX { .. } => localization.format("hint.axis_x", &[
+                ("left", &key(GameAction::MoveLeft)),
+                ("right", &key(GameAction::MoveRight)),
+            ]),
+            ClawControllerState::MovingZ { .. } => localization.format("hint.axis_z", &[
+                ("forward", &key(GameAction::MoveForward)),
+                ("back", &key(GameAction::MoveBack)),
+            ]),
+            _ => String::new(),
+        };
+    }
+}
+
 fn exit_system(
     mut commands: Commands
 ) {
-    commands.insert_resource(NextState(Gamemode::None));
+    commands.insert_resource(NextState(Gamemode::NONE));
 }
\ No newline at end of file
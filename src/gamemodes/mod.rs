@@ -1,10 +1,14 @@
 use bevy::{prelude::*, app::PluginGroupBuilder};
 
-use self::{speed_game::SpeedGamePlugin, gameplay::GameplayPlugin, number_game::NumberGamePlugin};
+use self::{speed_game::SpeedGamePlugin, gameplay::GameplayPlugin, number_game::NumberGamePlugin, survival::SurvivalPlugin, target::TargetPlugin, arcade::ArcadeGamePlugin};
 
 pub mod gameplay;
+pub mod definition;
 pub mod speed_game;
 pub mod number_game;
+pub mod survival;
+pub mod target;
+pub mod arcade;
 
 #[derive(Default)]
 pub struct GamemodePlugins;
@@ -14,6 +18,9 @@ impl PluginGroup for GamemodePlugins {
         group
             .add(GameplayPlugin)
             .add(SpeedGamePlugin)
-            .add(NumberGamePlugin);
+            .add(NumberGamePlugin)
+            .add(SurvivalPlugin)
+            .add(TargetPlugin)
+            .add(ArcadeGamePlugin);
     }
 }
\ No newline at end of file
@@ -1,101 +1,93 @@
+use std::time::Duration;
+
 use bevy::prelude::*;
 use iyes_loopless::prelude::*;
 
 use crate:: {
     gamemodes::gameplay::Gamemode,
-    GameState,
-    claw::{ReleaseClawEvent, ToyCatchEvent, ClawReturnedToBaseEvent},
-    game_results::GameResults,
-    ui::controls::in_game_text::InGameText, assets::audio::stop_background_audio_system,
+    claw::ReleaseClawEvent,
+    high_scores::Score,
+    localization::Localization,
+    assets::{audio::stop_background_audio_system, gamemode::{GamemodeAsset, ScoringRule}},
 };
 
+use super::definition::{GamemodeDefinition, GamemodeApp};
+
 #[derive(Default)]
 pub struct SpeedGamePlugin;
 
 impl Plugin for SpeedGamePlugin {
     fn build(&self, app: &mut App) {
         app
-            .add_enter_system(Gamemode::SpeedGame, setup_system)
+            .add_gamemode::<SpeedGameProgress>()
             .add_system_set(
                 ConditionSet::new()
-                    .run_in_state(Gamemode::SpeedGame)
-                    .with_system(register_toy_catch.run_on_event::<ToyCatchEvent>())
-                    .with_system(pause_timer.run_on_event::<ReleaseClawEvent>())
-                    .with_system(speed_game_system)
-                    .with_system(to_game_results_system.run_on_event::<ClawReturnedToBaseEvent>())
+                    .run_in_state(SpeedGameProgress::GAMEMODE)
                     .with_system(stop_background_audio_system.run_on_event::<ReleaseClawEvent>())
                     .into()
-            )
-            .add_exit_system(Gamemode::SpeedGame, exit_system);
+            );
     }
 }
 
 #[derive(Component, Clone)]
 pub struct SpeedGameProgress {
     pub timer: Timer,
-    pub toy_caught: bool
+    pub toys_caught: u8,
+    win_threshold: u8
 }
 
-impl SpeedGameProgress {
-    const TIME_TO_CATCH: f32 = 20.0;
-}
+impl GamemodeDefinition for SpeedGameProgress {
+    const GAMEMODE: Gamemode = Gamemode("SpeedGame");
+    const SCORING: ScoringRule = ScoringRule::TimeToFirstCatch;
 
-impl Default for SpeedGameProgress {
-    fn default() -> Self {
+    fn setup(params: &GamemodeAsset) -> Self {
         Self {
-            timer: Timer::from_seconds(SpeedGameProgress::TIME_TO_CATCH, false),
-            toy_caught: false
+            timer: Timer::from_seconds(params.time_limit, false),
+            toys_caught: 0,
+            win_threshold: params.win_threshold
         }
     }
-}
 
-fn setup_system(mut commands: Commands) {
-    commands.spawn()
-        .insert(SpeedGameProgress::default())
-        .insert(InGameText(String::new()));
-}
+    fn update(&mut self, delta: Duration) -> bool {
+        if !self.timer.paused() && self.timer.tick(delta).just_finished() {
+            self.timer.pause();
 
-fn speed_game_system(
-    time: Res<Time>,
-    mut query_progress: Query<&mut SpeedGameProgress>,
-    mut query_text: Query<&mut Text, With<InGameText>>,
-    mut events: EventWriter<ReleaseClawEvent>
-) {
-    if let Ok(mut progress) = query_progress.get_single_mut() {
-        if !progress.timer.paused() && progress.timer.tick(time.delta()).just_finished() {
-            progress.timer.pause();
-            events.send(ReleaseClawEvent);
+            return true;
         }
 
-        for mut text in query_text.iter_mut() {
-            let elapsed = progress.timer.elapsed_secs();
+        false
+    }
 
-            text.sections[0].value = format!("{:.2}", elapsed);
-        }
+    fn on_claw_release(&mut self) {
+        self.timer.pause();
     }
-}
 
-fn register_toy_catch(mut query: Query<&mut SpeedGameProgress>) {
-    let mut progress = query.get_single_mut().expect("register_toy_catch");
-    progress.toy_caught = true;
-}
+    fn on_toy_catch(&mut self, _toy: Entity) {
+        self.toys_caught += 1;
+    }
 
-fn pause_timer(mut query: Query<&mut SpeedGameProgress>) {
-    if let Ok(mut progress) = query.get_single_mut() {
-        progress.timer.pause();
+    /// Only one attempt is given
+    fn is_over(&self) -> bool {
+        self.timer.paused()
     }
-}
 
-fn to_game_results_system(mut commands: Commands) {
-    commands.insert_resource(NextState(GameState::GameResults));
-}
+    fn time_left(&self) -> f32 {
+        self.timer.duration().as_secs_f32() - self.timer.elapsed_secs()
+    }
 
-fn exit_system(
-    query: Query<(Entity, &SpeedGameProgress)>, 
-    mut commands: Commands
-) {
-    let (entity, progress) = query.get_single().expect("speed_game - exit_system");
+    fn hud_text(&self) -> String {
+        format!("{:.2}", self.timer.elapsed_secs())
+    }
 
-    commands.spawn().insert(GameResults::SpeedGame(progress.clone()));
-    commands.entity(entity).despawn_recursive();
+    fn is_win(&self) -> bool {
+        self.toys_caught >= self.win_threshold
+    }
+
+    fn summary(&self, localization: &Localization) -> String {
+        localization.format("results.seconds", &[("time", &format!("{:.2}", self.timer.elapsed_secs()))])
+    }
+
+    fn score(&self) -> Score {
+        Score::Time(self.timer.elapsed_secs())
+    }
 }
@@ -0,0 +1,105 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+
+use crate::{
+    assets::gamemode::{GamemodeAsset, ScoringRule},
+    high_scores::Score,
+    localization::Localization,
+    toy::top_up_toys_system,
+};
+
+use super::{gameplay::Gamemode, definition::{GamemodeDefinition, GamemodeApp}};
+
+#[derive(Default)]
+pub struct SurvivalPlugin;
+
+impl Plugin for SurvivalPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_gamemode::<SurvivalProgress>()
+            .add_system(top_up_toys_system.run_in_state(SurvivalProgress::GAMEMODE));
+    }
+}
+
+#[derive(Component, Clone)]
+pub struct SurvivalProgress {
+    time_left: f32,
+    /// Seconds the clock has been running
+    survived: f32,
+    catch_bonus: f32,
+    pub toys_caught: u8,
+    win_threshold: u8
+}
+
+impl GamemodeDefinition for SurvivalProgress {
+    const GAMEMODE: Gamemode = Gamemode("Survival");
+    const SCORING: ScoringRule = ScoringRule::Survival;
+
+    fn setup(params: &GamemodeAsset) -> Self {
+        Self {
+            time_left: params.time_limit,
+            survived: 0.0,
+            catch_bonus: params.catch_bonus,
+            toys_caught: 0,
+            win_threshold: params.win_threshold
+        }
+    }
+
+    fn update(&mut self, delta: Duration) -> bool {
+        if self.time_left <= 0.0 { return false; }
+
+        self.time_left -= delta.as_secs_f32();
+        self.survived += delta.as_secs_f32();
+
+        if self.time_left <= 0.0 {
+            self.time_left = 0.0;
+
+            return true;
+        }
+
+        false
+    }
+
+    /// A toy caught by the claw released at the last moment
+    /// still adds its bonus and keeps the round going
+    fn on_toy_catch(&mut self, _toy: Entity) {
+        self.toys_caught = self.toys_caught.saturating_add(1);
+        self.time_left += self.catch_bonus;
+    }
+
+    fn is_over(&self) -> bool {
+        self.time_left <= 0.0
+    }
+
+    fn time_left(&self) -> f32 {
+        self.time_left
+    }
+
+    fn hud_text(&self) -> String {
+        let remain = self.time_left.floor();
+
+        if remain > 0.0 {
+            format!("{:.0}", remain)
+        } else {
+            String::new()
+        }
+    }
+
+    fn is_win(&self) -> bool {
+        self.toys_caught >= self.win_threshold
+    }
+
+    fn summary(&self, localization: &Localization) -> String {
+        localization.plural(
+            "results.survived",
+            self.toys_caught as u64,
+            &[("time", &format!("{:.2}", self.survived))]
+        )
+    }
+
+    fn score(&self) -> Score {
+        Score::Survived(self.survived)
+    }
+}
@@ -0,0 +1,278 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+use rand::Rng;
+
+use crate::{
+    assets::{gamemode::{GamemodeAsset, ScoringRule}, theme::UiTheme},
+    clock::GameClock,
+    glue::Glue,
+    helpers::despawn_with,
+    high_scores::Score,
+    localization::Localization,
+    rng::GameRng,
+    toy::{Toy, EjectedToy},
+    ui::controls::spawn_backing_panel,
+};
+
+use super::{gameplay::Gamemode, definition::{GamemodeDefinition, GamemodeApp}};
+
+#[derive(Default)]
+pub struct TargetPlugin;
+
+impl Plugin for TargetPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_gamemode::<TargetProgress>()
+            .add_enter_system(TargetProgress::GAMEMODE, setup_hud_system)
+            .add_system_set(
+                ConditionSet::new()
+                    .run_in_state(TargetProgress::GAMEMODE)
+                    .with_system(pick_target_system)
+                    .with_system(hud_system)
+                    .into()
+            )
+            .add_exit_system(TargetProgress::GAMEMODE, despawn_with::<TargetHighlight>)
+            .add_exit_system(TargetProgress::GAMEMODE, despawn_with::<TargetHud>);
+    }
+}
+
+/// Toy the player has to catch
+#[derive(Component)]
+pub struct TargetToy;
+
+/// Glow and halo attached to the target toy
+#[derive(Component)]
+struct TargetHighlight;
+
+#[derive(Component)]
+struct TargetHud;
+
+#[derive(Component)]
+struct TargetHudText {
+    /// Shows the penalty instead of the hint while running
+    penalty: Timer,
+    misses: u8,
+}
+
+const TARGET_COLOR: Color = Color::rgb(1.0, 0.8, 0.1);
+const PENALTY_SHOWN_SECS: f32 = 2.0;
+
+#[derive(Component, Clone)]
+pub struct TargetProgress {
+    timer: Timer,
+    pub toys_caught: u8,
+    pub misses: u8,
+    win_threshold: u8,
+    miss_penalty: f32,
+    /// Picked by [`pick_target_system`] when there is none
+    target: Option<Entity>,
+}
+
+impl GamemodeDefinition for TargetProgress {
+    const GAMEMODE: Gamemode = Gamemode("TargetGame");
+    const SCORING: ScoringRule = ScoringRule::TargetCatches;
+
+    fn setup(params: &GamemodeAsset) -> Self {
+        Self {
+            timer: Timer::from_seconds(params.time_limit, false),
+            toys_caught: 0,
+            misses: 0,
+            win_threshold: params.win_threshold,
+            miss_penalty: params.miss_penalty,
+            target: None,
+        }
+    }
+
+    fn update(&mut self, delta: Duration) -> bool {
+        self.timer.tick(delta).just_finished()
+    }
+
+    /// Penalty is taken right away, so it may end the round on the next frame
+    fn on_toy_catch(&mut self, toy: Entity) {
+        if self.target == Some(toy) {
+            self.toys_caught += 1;
+            self.target = None;
+        } else {
+            let elapsed = self.timer.elapsed() + Duration::from_secs_f32(self.miss_penalty);
+
+            self.timer.set_elapsed(elapsed.min(self.timer.duration()));
+            self.misses = self.misses.saturating_add(1);
+        }
+    }
+
+    fn is_over(&self) -> bool {
+        self.timer.finished()
+    }
+
+    fn time_left(&self) -> f32 {
+        self.timer.duration().as_secs_f32() - self.timer.elapsed_secs()
+    }
+
+    fn hud_text(&self) -> String {
+        let remain = self.time_left().floor();
+
+        if remain > 0.0 {
+            format!("{:.0}", remain)
+        } else {
+            String::new()
+        }
+    }
+
+    fn is_win(&self) -> bool {
+        self.toys_caught >= self.win_threshold
+    }
+
+    fn summary(&self, localization: &Localization) -> String {
+        localization.plural("results.toys_caught", self.toys_caught as u64, &[])
+    }
+
+    fn score(&self) -> Score {
+        Score::Catches(self.toys_caught)
+    }
+}
+
+/// Marks a random toy left in the glass as the target when the previous one is caught.
+/// Uses the gameplay stream, so the choice is the same in a replay
+fn pick_target_system(
+    mut rng: ResMut<GameRng>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut progress_query: Query<&mut TargetProgress>,
+    toys: Query<Entity, (With<Toy>, Without<EjectedToy>)>,
+    previous: Query<Entity, With<TargetToy>>,
+    highlights: Query<Entity, With<TargetHighlight>>,
+    glue_query: Query<&Glue>,
+    mut commands: Commands,
+) {
+    let mut progress = match progress_query.get_single_mut() {
+        Ok(progress) => progress,
+        Err(_) => return,
+    };
+
+    if progress.target.is_some() { return; }
+
+    // Toy hanging on the claw is about to leave the glass
+    let candidates: Vec<Entity> = toys
+        .iter()
+        .filter(|toy| !glue_query.iter().any(|glue| glue.0 == *toy))
+        .collect();
+
+    if candidates.is_empty() { return; }
+
+    for entity in previous.iter() {
+        commands.entity(entity).remove::<TargetToy>();
+    }
+    for entity in highlights.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let target = candidates[rng.gameplay().gen_range(0..candidates.len())];
+
+    commands.entity(target)
+        .insert(TargetToy)
+        .with_children(|parent| {
+            parent.spawn()
+                .insert(TargetHighlight)
+                .insert_bundle(PointLightBundle {
+                    point_light: PointLight {
+                        color: TARGET_COLOR,
+                        intensity: 60.0,
+                        range: 1.0,
+                        ..default()
+                    },
+                    ..default()
+                });
+
+            parent.spawn()
+                .insert(TargetHighlight)
+                .insert_bundle(PbrBundle {
+                    mesh: meshes.add(Mesh::from(shape::Torus {
+                        radius: 0.12,
+                        ring_radius: 0.015,
+                        ..default()
+                    })),
+                    material: materials.add(StandardMaterial {
+                        base_color: TARGET_COLOR,
+                        emissive: TARGET_COLOR,
+                        unlit: true,
+                        ..default()
+                    }),
+                    transform: Transform::from_xyz(0.0, 0.55, 0.0),
+                    ..default()
+                });
+        });
+
+    progress.target = Some(target);
+}
+
+fn setup_hud_system(theme: Res<UiTheme>, localization: Res<Localization>, mut commands: Commands) {
+    commands.spawn()
+        .insert(TargetHud)
+        .insert_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect { top: Val::Percent(5.0), right: Val::Percent(5.0), ..default() },
+                align_items: AlignItems::Center,
+                padding: Rect::all(theme.px(10.0)),
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .with_children(|parent| {
+            spawn_backing_panel(parent, &theme);
+
+            parent.spawn_bundle(NodeBundle {
+                style: Style {
+                    size: Size::new(theme.px(24.0), theme.px(24.0)),
+                    margin: Rect { right: theme.px(10.0), ..default() },
+                    ..default()
+                },
+                color: TARGET_COLOR.into(),
+                ..default()
+            });
+
+            parent.spawn()
+                .insert(TargetHudText {
+                    penalty: Timer::from_seconds(PENALTY_SHOWN_SECS, false),
+                    misses: 0,
+                })
+                .insert_bundle(TextBundle {
+                    text: Text::with_section(
+                        localization.get("target.hint"),
+                        TextStyle {
+                            font: theme.font.clone(),
+                            font_size: theme.text_font_size,
+                            color: theme.text_color,
+                        },
+                        default()
+                    ),
+                    ..default()
+                });
+        });
+}
+
+/// Shows the hint, or the penalty for a while after a wrong toy is caught
+fn hud_system(
+    clock: Res<GameClock>,
+    localization: Res<Localization>,
+    progress_query: Query<&TargetProgress>,
+    mut texts: Query<(&mut Text, &mut TargetHudText)>,
+) {
+    if let (Ok(progress), Ok((mut text, mut hud))) = (progress_query.get_single(), texts.get_single_mut()) {
+        if progress.misses != hud.misses {
+            hud.misses = progress.misses;
+            hud.penalty.reset();
+        } else if hud.misses > 0 {
+            hud.penalty.tick(clock.delta());
+        }
+
+        text.sections[0].value = if hud.misses > 0 && !hud.penalty.finished() {
+            localization.format("target.penalty", &[("time", &progress.miss_penalty)])
+        } else {
+            localization.get("target.hint")
+        };
+    }
+}
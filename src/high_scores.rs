@@ -0,0 +1,238 @@
+use bevy::{prelude::*, utils::HashMap};
+use iyes_loopless::prelude::*;
+use serde::{Deserialize, Serialize};
+use strum_macros::Display;
+
+use crate::{
+    GameState,
+    storage,
+    settings::Settings,
+    helpers::despawn_with,
+    game_results::GameResults,
+    localization::Localization,
+    assets::theme::UiTheme,
+    gamemodes::definition::GamemodeRegistry,
+    ui::controls::{
+        ButtonPressEvent,
+        menu::{CMUIMenu, MenuStack, MenuStackEvent},
+        button::CMUIButton,
+        in_game_text::InGameText,
+    },
+};
+
+#[derive(Default)]
+pub struct HighScoresPlugin;
+
+impl Plugin for HighScoresPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(HighScores::load())
+            .add_system(record_system.run_in_state(GameState::GameResults))
+            .add_enter_system(GameState::HighScores, high_scores_menu_system)
+            .add_system(handle_menu_click_system.run_in_state(GameState::HighScores))
+            .add_system(clear_table_system.run_in_state(GameState::HighScores))
+            .add_exit_system(GameState::HighScores, despawn_with::<CMUIMenu>)
+            .add_exit_system(GameState::HighScores, despawn_with::<HighScoreTable>);
+    }
+}
+
+/// Result of a won round that can be compared with others of the same gamemode
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Score {
+    /// Seconds spent, less is better
+    Time(f32),
+    /// Toys caught, more is better
+    Catches(u8),
+    /// Seconds survived, more is better
+    Survived(f32),
+    /// Tickets won, more is better
+    Tickets(u32),
+}
+
+impl Score {
+    fn is_better_than(&self, other: &Score) -> bool {
+        match (self, other) {
+            (Score::Time(a), Score::Time(b)) => a < b,
+            (Score::Catches(a), Score::Catches(b)) => a > b,
+            (Score::Survived(a), Score::Survived(b)) => a > b,
+            (Score::Tickets(a), Score::Tickets(b)) => a > b,
+            _ => false,
+        }
+    }
+
+    pub fn text(&self, localization: &Localization) -> String {
+        match self {
+            Score::Time(seconds) | Score::Survived(seconds) => localization.format("score.time", &[("time", &format!("{:.2}", seconds))]),
+            Score::Catches(toys) => localization.plural("score.toys", *toys as u64, &[]),
+            Score::Tickets(tickets) => localization.plural("score.tickets", *tickets as u64, &[]),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct HighScoreRecord {
+    pub player: String,
+    pub score: Score,
+    /// Local date in `YYYY-MM-DD` format
+    pub date: String,
+}
+
+/// Best results per gamemode variant, persisted in the user data directory
+#[derive(Serialize, Deserialize, Default)]
+pub struct HighScores(HashMap<String, Vec<HighScoreRecord>>);
+
+impl HighScores {
+    const FILE_NAME: &'static str = "high_scores.ron";
+    const TABLE_SIZE: usize = 10;
+
+    fn load() -> Self {
+        storage::load(HighScores::FILE_NAME).unwrap_or_default()
+    }
+
+    fn save(&self) {
+        storage::save(HighScores::FILE_NAME, self);
+    }
+
+    pub fn table(&self, gamemode: &str) -> &[HighScoreRecord] {
+        self.0.get(gamemode).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Adds the record to the table and returns its rank starting from 1,
+    /// or `None` if the record didn't make it to the table
+    pub fn insert(&mut self, gamemode: &str, record: HighScoreRecord) -> Option<usize> {
+        let table = self.0.entry(gamemode.to_string()).or_default();
+        let index = table
+            .iter()
+            .position(|existing| record.score.is_better_than(&existing.score))
+            .unwrap_or(table.len());
+
+        if index >= HighScores::TABLE_SIZE { return None; }
+
+        table.insert(index, record);
+        table.truncate(HighScores::TABLE_SIZE);
+
+        Some(index + 1)
+    }
+}
+
+#[derive(Component)]
+struct HighScoreTable;
+
+#[derive(Display)]
+enum HighScoresButton {
+    Back
+}
+
+fn record_system(
+    mut high_scores: ResMut<HighScores>,
+    settings: Res<Settings>,
+    query: Query<(Entity, &GameResults), Added<GameResults>>,
+    mut commands: Commands,
+) {
+    if let Ok((entity, results)) = query.get_single() {
+        if results.is_win && !results.is_playback {
+            let record = HighScoreRecord {
+                player: settings.player_name.clone(),
+                score: results.score,
+                date: chrono::Local::now().format("%Y-%m-%d").to_string(),
+            };
+
+            if let Some(rank) = high_scores.insert(&results.gamemode, record) {
+                high_scores.save();
+                commands.entity(entity).insert(InGameText(format!("#{}", rank)));
+            }
+        }
+    }
+}
+
+fn table_text(records: &[HighScoreRecord], localization: &Localization) -> String {
+    if records.is_empty() {
+        return localization.get("high_scores.empty");
+    }
+
+    records
+        .iter()
+        .enumerate()
+        .map(|(index, record)| localization.format("high_scores.record", &[
+            ("rank", &(index + 1)),
+            ("player", &record.player),
+            ("score", &record.score.text(localization)),
+            ("date", &record.date),
+        ]))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Lists gamemodes, table of the picked one is shown in its submenu
+fn high_scores_menu_system(
+    theme: Res<UiTheme>,
+    registry: Res<GamemodeRegistry>,
+    mut commands: Commands,
+) {
+    let mut buttons: Vec<CMUIButton> = registry
+        .iter()
+        .map(|entry| CMUIButton::new(&entry.id, &entry.params.label))
+        .collect();
+
+    buttons.push(CMUIButton::new(HighScoresButton::Back, "ui.back"));
+    buttons[0] = buttons[0].clone().selected();
+
+    commands.spawn()
+        .insert(CMUIMenu::new("high_scores.title", buttons).columns(2));
+
+    commands.spawn()
+        .insert(HighScoreTable)
+        .insert_bundle(TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect { top: Val::Percent(30.0), left: Val::Percent(10.0), ..default() },
+                ..default()
+            },
+            text: Text::with_section(
+                "",
+                TextStyle {
+                    font: theme.font.clone(),
+                    font_size: theme.text_font_size,
+                    color: theme.text_color,
+                },
+                default()
+            ),
+            ..default()
+        });
+}
+
+fn handle_menu_click_system(
+    high_scores: Res<HighScores>,
+    registry: Res<GamemodeRegistry>,
+    localization: Res<Localization>,
+    mut events: EventReader<ButtonPressEvent>,
+    mut stack_events: EventWriter<MenuStackEvent>,
+    mut table_query: Query<&mut Text, With<HighScoreTable>>,
+    mut commands: Commands
+) {
+    for event in events.iter() {
+        if event.0 == HighScoresButton::Back.to_string() {
+            commands.insert_resource(NextState(GameState::MainMenu));
+        } else if let Some(entry) = registry.get(&event.0) {
+            stack_events.send(MenuStackEvent::Push(
+                CMUIMenu::new(&entry.params.label, vec![CMUIButton::back().selected()])
+            ));
+
+            if let Ok(mut text) = table_query.get_single_mut() {
+                text.sections[0].value = table_text(high_scores.table(&entry.id), &localization);
+            }
+        }
+    }
+}
+
+/// Hides the table when returning to the list of gamemodes
+fn clear_table_system(
+    stack: Res<MenuStack>,
+    mut table_query: Query<&mut Text, With<HighScoreTable>>,
+) {
+    if stack.is_changed() && stack.depth() == 0 {
+        if let Ok(mut text) = table_query.get_single_mut() {
+            text.sections[0].value.clear();
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use std::fmt::Display;
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    settings::Settings,
+    assets::locale::{LocaleAsset, PluralCategory},
+};
+
+/// Keeps [`Localization`] filled with the strings of the language chosen in [`Settings`].
+/// Tables are reloaded when the language is switched or a locale file is changed
+#[derive(Default)]
+pub struct LocalizationPlugin;
+
+impl Plugin for LocalizationPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Localization>()
+            .add_system(update_localization_system);
+    }
+}
+
+/// Strings of the current language. Keys missing in it are looked up in
+/// [`Localization::FALLBACK`] language, text that isn't a key is returned as is,
+/// so names and numbers can be passed everywhere a key is expected.
+///
+/// Parameters are written as `{name}` in strings, plural strings get `{count}`
+#[derive(Default)]
+pub struct Localization {
+    language: String,
+    /// Codes and names of loaded languages
+    languages: Vec<(String, String)>,
+    strings: HashMap<String, String>,
+    plurals: HashMap<String, HashMap<PluralCategory, String>>,
+}
+
+impl Localization {
+    pub const FALLBACK: &'static str = "en";
+
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    /// Codes and names of available languages, sorted by code
+    pub fn languages(&self) -> &[(String, String)] {
+        &self.languages
+    }
+
+    pub fn get(&self, key: &str) -> String {
+        self.strings.get(key).cloned().unwrap_or_else(|| key.to_string())
+    }
+
+    pub fn format(&self, key: &str, args: &[(&str, &dyn Display)]) -> String {
+        substitute(self.get(key), args)
+    }
+
+    /// String of the plural category the count falls into in the current language
+    pub fn plural(&self, key: &str, count: u64, args: &[(&str, &dyn Display)]) -> String {
+        let text = self.plurals
+            .get(key)
+            .and_then(|forms| {
+                forms
+                    .get(&plural_category(&self.language, count))
+                    .or_else(|| forms.get(&PluralCategory::Other))
+            })
+            .cloned()
+            .unwrap_or_else(|| key.to_string());
+
+        substitute(text, args).replace("{count}", &count.to_string())
+    }
+
+    fn load(&mut self, language: &str, locales: &Assets<LocaleAsset>) {
+        let tables: Vec<&LocaleAsset> = locales.iter().map(|(_, locale)| locale).collect();
+
+        self.language = language.to_string();
+        self.languages = tables.iter().map(|locale| (locale.code.clone(), locale.name.clone())).collect();
+        self.languages.sort();
+        self.strings.clear();
+        self.plurals.clear();
+
+        // Fallback goes first, so the strings of the language replace it
+        for code in [Localization::FALLBACK, language] {
+            if let Some(locale) = tables.iter().find(|locale| locale.code == code) {
+                self.strings.extend(locale.strings.iter().map(|(key, value)| (key.clone(), value.clone())));
+                self.plurals.extend(locale.plurals.iter().map(|(key, value)| (key.clone(), value.clone())));
+            }
+        }
+    }
+}
+
+fn substitute(mut text: String, args: &[(&str, &dyn Display)]) -> String {
+    for (name, value) in args {
+        text = text.replace(&format!("{{{}}}", name), &value.to_string());
+    }
+
+    text
+}
+
+/// Plural rules of CLDR for the languages the game may be translated to,
+/// others use `One` for 1 and `Other` for the rest
+fn plural_category(language: &str, count: u64) -> PluralCategory {
+    match language {
+        "ja" | "ko" | "zh" => PluralCategory::Other,
+        "fr" => if count <= 1 { PluralCategory::One } else { PluralCategory::Other },
+        "ru" | "uk" | "be" => match (count % 10, count % 100) {
+            (1, rem100) if rem100 != 11 => PluralCategory::One,
+            (2..=4, rem100) if !(12..=14).contains(&rem100) => PluralCategory::Few,
+            _ => PluralCategory::Many,
+        },
+        "pl" => match (count % 10, count % 100) {
+            _ if count == 1 => PluralCategory::One,
+            (2..=4, rem100) if !(12..=14).contains(&rem100) => PluralCategory::Few,
+            _ => PluralCategory::Many,
+        },
+        _ => if count == 1 { PluralCategory::One } else { PluralCategory::Other },
+    }
+}
+
+fn update_localization_system(
+    settings: Res<Settings>,
+    locales: Res<Assets<LocaleAsset>>,
+    mut events: EventReader<AssetEvent<LocaleAsset>>,
+    mut localization: ResMut<Localization>,
+) {
+    let is_reloaded = events.iter().count() > 0;
+
+    if is_reloaded || settings.language != localization.language() {
+        localization.load(&settings.language, &locales);
+    }
+}
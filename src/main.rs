@@ -1,16 +1,25 @@
+use arcade::ArcadePlugin;
 use assets::AssetLoaderPlugins;
-use bevy::{prelude::*, DefaultPlugins};
+use bevy::{prelude::*, asset::AssetServerSettings, DefaultPlugins};
 use bevy_kira_audio::AudioPlugin;
 use bevy_rapier3d::prelude::*;
 use camera::CameraPlugin;
 use claw::ClawPlugin;
 use claw_machine::ClawMachinePlugin;
+use clock::ClockPlugin;
 use controls::ControlsPlugin;
+use daily::DailyPlugin;
 use game_results::GameResultsPlugin;
 use gamemodes::GamemodePlugins;
 use glue::GluePlugin;
+use high_scores::HighScoresPlugin;
 use iyes_loopless::prelude::*;
+use localization::LocalizationPlugin;
 use movement::MovementPlugin;
+use pause::PausePlugin;
+use replay::ReplayPlugin;
+use rng::RngPlugin;
+use settings::SettingsPlugin;
 use room::RoomPlugin;
 use toy::ToyPlugin;
 use ui::UIPlugins;
@@ -29,18 +38,38 @@ mod claw_machine;
 mod helpers;
 mod gamemodes;
 mod game_results;
+mod high_scores;
+mod storage;
+mod clock;
+mod replay;
+mod simulation;
+mod rng;
+mod pause;
+mod settings;
+mod localization;
+mod arcade;
+mod daily;
 
 fn main() {
+    if std::env::args().any(|arg| arg == "--headless") {
+        return run_headless();
+    }
+
     App::new()
-        .insert_resource(Msaa { samples: 4 })
         .insert_resource(ClearColor(Color::rgb(0.9, 0.9, 0.9)))
 
         .add_loopless_state(GameState::Loading)
 
+        .add_plugin(SettingsPlugin)
+        // Themes, locales and gamemodes are reloaded when their files are edited
+        .insert_resource(AssetServerSettings { watch_for_changes: true, ..default() })
         .add_plugins(DefaultPlugins)
+        .add_plugin(RngPlugin)
         .add_plugin(AudioPlugin)
         .add_plugins(AssetLoaderPlugins)
+        .add_plugin(LocalizationPlugin)
         .add_plugins(UIPlugins)
+        .add_plugin(ClockPlugin)
         .add_plugin(CameraPlugin)
         .add_plugin(MovementPlugin)
         .add_plugin(ControlsPlugin)
@@ -51,16 +80,37 @@ fn main() {
         .add_plugin(ClawMachinePlugin)
         .add_plugins(GamemodePlugins)
         .add_plugin(GameResultsPlugin)
+        .add_plugin(HighScoresPlugin)
+        .add_plugin(ArcadePlugin)
+        .add_plugin(DailyPlugin)
+        .add_plugin(ReplayPlugin)
+        .add_plugin(PausePlugin)
 
         .add_plugin(RapierPhysicsPlugin::<NoUserData>::default())
         // .add_plugin(RapierDebugRenderPlugin::default())
         .run();
 }
 
+/// Runs the claw simulation self check without a window
+/// and exits with non-zero code on failure
+fn run_headless() {
+    match simulation::run_self_check(rng::seed_from_args().unwrap_or_default()) {
+        Ok(log) => println!("Simulation passed: {:?}", log),
+        Err(error) => {
+            eprintln!("Simulation failed: {}", error);
+            std::process::exit(1);
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum GameState {
     Loading,
     MainMenu,
     InGame,
-    GameResults
+    GameResults,
+    HighScores,
+    KeyBindings,
+    Settings,
+    PrizeCounter
 }
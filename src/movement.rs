@@ -1,5 +1,7 @@
 use bevy::prelude::*;
 
+use crate::{clock::GameClock, controls::ClawInput};
+
 #[derive(Default)]
 pub struct MovementPlugin;
 
@@ -7,28 +9,33 @@ impl Plugin for MovementPlugin {
     fn build(&self, app: &mut App) {
         app
             .insert_resource(WASDMovementSettings { target_index: 0 })
-            .add_system(wasd_movement_system);
+            .add_system(wasd_movement_system)
+            .add_system(axis_movement_system);
     }
 }
 
 #[derive(Component)]
 pub struct WASDMovement;
 
+/// Moves along one axis only, by the input of that axis
+#[derive(Component)]
+pub struct AxisMovement(pub MovementAxis);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MovementAxis {
+    X,
+    Z,
+}
+
+const SPEED: f32 = 1.2;
+
 struct WASDMovementSettings {
     target_index: usize,
 }
 
-pub const MOVEMENT_KEYS: [KeyCode; 6] = [
-    KeyCode::W,
-    KeyCode::A,
-    KeyCode::S,
-    KeyCode::D,
-    KeyCode::LShift,
-    KeyCode::Space
-];
-
 fn wasd_movement_system(
-    time: Res<Time>,
+    clock: Res<GameClock>,
+    input: Res<ClawInput>,
     keyboard: Res<Input<KeyCode>>,
     mut query: Query<&mut Transform, With<WASDMovement>>,
     mut settings: ResMut<WASDMovementSettings>,
@@ -39,24 +46,14 @@ fn wasd_movement_system(
     for (index, mut transform) in query_iter.enumerate() {
         if index != settings.target_index { continue; }
 
-        if keyboard.any_pressed(MOVEMENT_KEYS.into_iter()) {
-            const SPEED: f32 = 1.2;
-            let distance = SPEED * time.delta_seconds();
+        if input.x != 0.0 || input.z != 0.0 || keyboard.any_pressed([KeyCode::LShift, KeyCode::Space]) {
+            let distance = SPEED * clock.delta_seconds();
 
             let [mut x, mut y, mut z] = transform.translation.to_array();
 
-            if keyboard.pressed(KeyCode::W) {
-                z -= distance;
-            }
-            if keyboard.pressed(KeyCode::S) {
-                z += distance;
-            }
-            if keyboard.pressed(KeyCode::A) {
-                x -= distance;
-            }
-            if keyboard.pressed(KeyCode::D) {
-                x += distance;
-            }
+            x += input.x * distance;
+            z += input.z * distance;
+
             if keyboard.pressed(KeyCode::LShift) {
                 y -= distance;
             }
@@ -79,4 +76,19 @@ fn wasd_movement_system(
 
         println!("settings.target_index: {}", settings.target_index)
     }
-}
\ No newline at end of file
+}
+
+fn axis_movement_system(
+    clock: Res<GameClock>,
+    input: Res<ClawInput>,
+    mut query: Query<(&mut Transform, &AxisMovement)>,
+) {
+    let distance = SPEED * clock.delta_seconds();
+
+    for (mut transform, movement) in query.iter_mut() {
+        match movement.0 {
+            MovementAxis::X => transform.translation.x += input.x * distance,
+            MovementAxis::Z => transform.translation.z += input.z * distance,
+        }
+    }
+}
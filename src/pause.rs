@@ -0,0 +1,124 @@
+use bevy::prelude::*;
+use bevy_kira_audio::AudioChannel;
+use iyes_loopless::prelude::*;
+use strum_macros::Display;
+
+use crate::{
+    GameState,
+    helpers::despawn_with,
+    clock::GameClock,
+    controls::GameAction,
+    assets::audio::BackgroundAudioChannel,
+    gamemodes::definition::RoundProgress,
+    ui::controls::{ButtonPressEvent, menu::CMUIMenu, button::CMUIButton},
+};
+
+/// Pauses the round with [`GameAction::Pause`] and shows the pause menu.
+/// While paused [`GameClock`] stands still, physics is suspended
+/// and background audio is paused
+#[derive(Default)]
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_loopless_state(PauseState::Running)
+            .add_system(toggle_pause_system.run_in_state(GameState::InGame))
+            .add_enter_system(PauseState::Paused, pause_system)
+            .add_system(handle_menu_click_system.run_in_state(PauseState::Paused))
+            .add_exit_system(PauseState::Paused, resume_system)
+            .add_exit_system(PauseState::Paused, despawn_with::<PauseMenu>)
+            .add_exit_system(GameState::InGame, clear_round_aborted_system);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PauseState {
+    Running,
+    Paused,
+}
+
+/// Exists while the round is being left from the pause menu,
+/// so it doesn't get results and its replay isn't saved
+pub struct RoundAborted;
+
+#[derive(Component)]
+struct PauseMenu;
+
+#[derive(Display)]
+enum PauseButton {
+    Resume,
+    Restart,
+    Quit,
+}
+
+fn toggle_pause_system(
+    actions: Res<Input<GameAction>>,
+    state: Res<CurrentState<PauseState>>,
+    mut commands: Commands,
+) {
+    if actions.just_pressed(GameAction::Pause) {
+        commands.insert_resource(NextState(match state.0 {
+            PauseState::Running => PauseState::Paused,
+            PauseState::Paused => PauseState::Running,
+        }));
+    }
+}
+
+fn pause_system(
+    audio: Res<AudioChannel<BackgroundAudioChannel>>,
+    mut clock: ResMut<GameClock>,
+    mut commands: Commands,
+) {
+    clock.pause();
+    audio.pause();
+
+    commands.spawn()
+        .insert(PauseMenu)
+        .insert(CMUIMenu::new("pause.title", vec![
+            CMUIButton::new(PauseButton::Resume, "pause.resume").selected(),
+            CMUIButton::new(PauseButton::Restart, "pause.restart"),
+            CMUIButton::new(PauseButton::Quit, "pause.main_menu"),
+        ]));
+}
+
+fn resume_system(
+    audio: Res<AudioChannel<BackgroundAudioChannel>>,
+    mut clock: ResMut<GameClock>,
+) {
+    clock.resume();
+    audio.resume();
+}
+
+fn handle_menu_click_system(
+    mut events: EventReader<ButtonPressEvent>,
+    progress_query: Query<Entity, With<RoundProgress>>,
+    mut commands: Commands,
+) {
+    for event in events.iter() {
+        let next_state = if event.0 == PauseButton::Restart.to_string() {
+            // Re-entering the state runs its exit and enter systems, which starts a new round
+            Some(GameState::InGame)
+        } else if event.0 == PauseButton::Quit.to_string() {
+            Some(GameState::MainMenu)
+        } else {
+            None
+        };
+
+        if let Some(next_state) = next_state {
+            // Without progress the gamemode has no results to report
+            for entity in progress_query.iter() {
+                commands.entity(entity).despawn_recursive();
+            }
+
+            commands.insert_resource(RoundAborted);
+            commands.insert_resource(NextState(next_state));
+        }
+
+        commands.insert_resource(NextState(PauseState::Running));
+    }
+}
+
+fn clear_round_aborted_system(mut commands: Commands) {
+    commands.remove_resource::<RoundAborted>();
+}
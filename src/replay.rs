@@ -0,0 +1,212 @@
+use std::{path::Path, time::Duration};
+
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    GameState,
+    storage,
+    assets::gamemode::GamemodeAsset,
+    clock::{GameClock, ClockSystem},
+    controls::{ClawInput, ClawInputOverride},
+    gamemodes::{gameplay::GameSettings, definition::GamemodeRegistry},
+    rng::GameRng,
+    pause::RoundAborted,
+    settings::ControlScheme,
+};
+
+/// Records every round to a replay file and plays replays back
+/// in place of the keyboard. A replay can be started from the main menu
+/// or with `--replay <path>` command line argument
+#[derive(Default)]
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(PendingReplay::from_args())
+            .init_resource::<ReplayRecorder>()
+            .init_resource::<RecordedReplay>()
+            .add_system(start_pending_replay_system.run_in_state(GameState::MainMenu))
+            .add_enter_system(GameState::InGame, start_round_system.label(StartRoundSystem))
+            .add_system_to_stage(CoreStage::First, playback_system.after(ClockSystem))
+            .add_system(record_system.run_in_state(GameState::InGame))
+            .add_exit_system(GameState::InGame, finish_round_system);
+    }
+}
+
+/// Records [`GameSettings`] of the round on [`GameState::InGame`] enter,
+/// systems that change them have to run before
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StartRoundSystem;
+
+/// Everything needed to reproduce a round exactly
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Replay {
+    pub version: u32,
+    /// Round seed of the [`GameRng`]
+    pub seed: u64,
+    /// Id of the gamemode variant
+    pub gamemode: String,
+    pub params: GamemodeAsset,
+    /// Older replays were recorded with free movement
+    #[serde(default)]
+    pub control_scheme: ControlScheme,
+    pub frames: Vec<ReplayFrame>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct ReplayFrame {
+    pub delta: Duration,
+    pub input: ClawInput,
+}
+
+impl Replay {
+    pub const VERSION: u32 = 1;
+    pub const LAST_REPLAY: &'static str = "last_replay.ron";
+
+    fn new(seed: u64, gamemode: String, params: GamemodeAsset, control_scheme: ControlScheme) -> Self {
+        Self { version: Replay::VERSION, seed, gamemode, params, control_scheme, frames: Vec::new() }
+    }
+
+    pub fn load(path: &Path) -> Option<Replay> {
+        let replay: Replay = storage::load_from(path)?;
+
+        if replay.version != Replay::VERSION {
+            warn!("Replay {:?} has unsupported version {}", path, replay.version);
+
+            return None;
+        }
+
+        Some(replay)
+    }
+
+    pub fn load_last() -> Option<Replay> {
+        Replay::load(&storage::data_path(Replay::LAST_REPLAY)?)
+    }
+
+    pub fn last_exists() -> bool {
+        storage::data_path(Replay::LAST_REPLAY).map_or(false, |path| path.exists())
+    }
+
+    fn save(&self) {
+        let timestamp = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S");
+
+        storage::save(Replay::LAST_REPLAY, self);
+        storage::save(&format!("replays/{}.ron", timestamp), self);
+    }
+}
+
+/// Replay of the last finished round that wasn't a playback
+#[derive(Default)]
+pub struct RecordedReplay(pub Option<Replay>);
+
+/// Replay to be played as soon as the main menu is shown
+#[derive(Default)]
+pub struct PendingReplay(pub Option<Replay>);
+
+impl PendingReplay {
+    fn from_args() -> Self {
+        let path = std::env::args()
+            .skip_while(|arg| arg != "--replay")
+            .nth(1);
+
+        Self(path.and_then(|path| Replay::load(Path::new(&path))))
+    }
+}
+
+/// Replay of the running round
+struct ReplayPlayback {
+    replay: Replay,
+    frame: usize,
+}
+
+/// Replay of the running round being recorded
+#[derive(Default)]
+struct ReplayRecorder(Option<Replay>);
+
+fn start_pending_replay_system(
+    registry: Res<GamemodeRegistry>,
+    mut pending: ResMut<PendingReplay>,
+    mut settings: ResMut<GameSettings>,
+    mut commands: Commands,
+) {
+    if let Some(replay) = pending.0.take() {
+        if let Some(gamemode) = registry.gamemode(replay.params.scoring) {
+            settings.gamemode = gamemode;
+            settings.control_scheme = replay.control_scheme;
+            settings.is_playback = true;
+            settings.variant = replay.gamemode.clone();
+            settings.params = Some(replay.params.clone());
+
+            commands.insert_resource(ReplayPlayback { replay, frame: 0 });
+            commands.insert_resource(ClawInputOverride);
+            commands.insert_resource(NextState(GameState::InGame));
+        }
+    }
+}
+
+fn start_round_system(
+    settings: Res<GameSettings>,
+    playback: Option<Res<ReplayPlayback>>,
+    mut rng: ResMut<GameRng>,
+    mut recorder: ResMut<ReplayRecorder>,
+) {
+    if let Some(playback) = playback {
+        rng.start_round(Some(playback.replay.seed));
+    } else {
+        let seed = rng.start_round(settings.seed);
+
+        recorder.0 = settings.params
+            .clone()
+            .map(|params| Replay::new(seed, settings.variant.clone(), params, settings.control_scheme));
+    }
+}
+
+fn playback_system(
+    playback: Option<ResMut<ReplayPlayback>>,
+    mut clock: ResMut<GameClock>,
+    mut input: ResMut<ClawInput>,
+) {
+    if let Some(mut playback) = playback {
+        if clock.is_paused() {
+            *input = ClawInput::default();
+        } else if let Some(frame) = playback.replay.frames.get(playback.frame).copied() {
+            clock.set_delta(frame.delta);
+            *input = frame.input;
+            playback.frame += 1;
+        } else {
+            *input = ClawInput::default();
+        }
+    }
+}
+
+fn record_system(
+    clock: Res<GameClock>,
+    input: Res<ClawInput>,
+    mut recorder: ResMut<ReplayRecorder>,
+) {
+    if clock.is_paused() { return; }
+
+    if let Some(replay) = &mut recorder.0 {
+        replay.frames.push(ReplayFrame { delta: clock.delta(), input: *input });
+    }
+}
+
+fn finish_round_system(
+    aborted: Option<Res<RoundAborted>>,
+    mut recorder: ResMut<ReplayRecorder>,
+    mut recorded: ResMut<RecordedReplay>,
+    mut commands: Commands,
+) {
+    if let Some(replay) = recorder.0.take() {
+        if aborted.is_none() {
+            replay.save();
+            recorded.0 = Some(replay);
+        }
+    }
+
+    commands.remove_resource::<ReplayPlayback>();
+    commands.remove_resource::<ClawInputOverride>();
+}
@@ -0,0 +1,100 @@
+use bevy::prelude::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::Deserialize;
+
+use crate::storage;
+
+/// Provides [`GameRng`] seeded from `--seed <number>` command line argument,
+/// `seed` field of `config.ron` in the user data directory or entropy
+#[derive(Default)]
+pub struct RngPlugin;
+
+impl Plugin for RngPlugin {
+    fn build(&self, app: &mut App) {
+        let seed = seed_from_args()
+            .or_else(|| storage::load::<RngConfig>(RngConfig::FILE_NAME).and_then(|config| config.seed))
+            .unwrap_or_else(rand::random);
+
+        info!("Game seed: {}", seed);
+
+        app.insert_resource(GameRng::new(seed));
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct RngConfig {
+    seed: Option<u64>,
+}
+
+impl RngConfig {
+    const FILE_NAME: &'static str = "config.ron";
+}
+
+pub fn seed_from_args() -> Option<u64> {
+    std::env::args()
+        .skip_while(|arg| arg != "--seed")
+        .nth(1)
+        .and_then(|seed| seed.parse().ok())
+}
+
+/// The only source of randomness in the game.
+/// Every round gets its own seed drawn from the session seed (or taken from a replay),
+/// which reseeds two independent streams:
+/// - `gameplay` for everything affecting the outcome (toy layout)
+/// - `cosmetic` for everything else (sound choice)
+///
+/// so cosmetic randomness never disturbs gameplay determinism
+pub struct GameRng {
+    seed: u64,
+    round_seed: u64,
+    session: StdRng,
+    gameplay: StdRng,
+    cosmetic: StdRng,
+}
+
+impl GameRng {
+    /// Mixed into the round seed to decorrelate the cosmetic stream from the gameplay one
+    const COSMETIC_SALT: u64 = 0x9e37_79b9_7f4a_7c15;
+
+    pub fn new(seed: u64) -> Self {
+        let mut session = StdRng::seed_from_u64(seed);
+        let round_seed = session.gen();
+
+        Self {
+            seed,
+            round_seed,
+            session,
+            gameplay: StdRng::seed_from_u64(round_seed),
+            cosmetic: StdRng::seed_from_u64(round_seed ^ GameRng::COSMETIC_SALT),
+        }
+    }
+
+    /// Seed of the whole session
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Seed of the running round
+    pub fn round_seed(&self) -> u64 {
+        self.round_seed
+    }
+
+    /// Reseeds the streams for a new round. A new round seed is drawn
+    /// from the session unless given (e.g. by a replay). Returns the round seed
+    pub fn start_round(&mut self, seed: Option<u64>) -> u64 {
+        self.round_seed = seed.unwrap_or_else(|| self.session.gen());
+        self.gameplay = StdRng::seed_from_u64(self.round_seed);
+        self.cosmetic = StdRng::seed_from_u64(self.round_seed ^ GameRng::COSMETIC_SALT);
+
+        self.round_seed
+    }
+
+    pub fn gameplay(&mut self) -> &mut StdRng {
+        &mut self.gameplay
+    }
+
+    pub fn cosmetic(&mut self) -> &mut StdRng {
+        &mut self.cosmetic
+    }
+}
@@ -0,0 +1,239 @@
+use std::ops::RangeInclusive;
+
+use bevy::{prelude::*, window::{PresentMode, WindowMode}};
+use bevy_kira_audio::AudioChannel;
+use serde::{Deserialize, Serialize};
+use strum_macros::EnumIter;
+
+use crate::{
+    storage,
+    assets::{
+        audio::{BackgroundAudioChannel, GlassAudioChannel, DropAudioChannel, UiAudioChannel},
+        theme::UiTheme,
+    },
+};
+
+/// Loads [`Settings`] and applies them. Must be added before `DefaultPlugins`
+/// for the window to be created with the saved mode
+#[derive(Default)]
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        let settings = Settings::load();
+
+        app
+            .insert_resource(WindowDescriptor {
+                mode: settings.window_mode(),
+                present_mode: settings.present_mode(),
+                ..default()
+            })
+            .insert_resource(Msaa { samples: settings.msaa })
+            .insert_resource(settings)
+            .add_system(apply_settings_system);
+    }
+}
+
+/// Player options stored in `settings.ron` of the user data directory
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Settings {
+    pub volume: Volume,
+    /// Samples of multisample anti-aliasing, 1 (off) or 4
+    pub msaa: u32,
+    pub fullscreen: bool,
+    pub vsync: bool,
+    pub show_fps: bool,
+    /// Multiplier of camera rotation and panning speed
+    pub camera_sensitivity: f32,
+    /// Name written to the high score table
+    pub player_name: String,
+    /// Code of the UI language, see [`Localization`](crate::localization::Localization)
+    pub language: String,
+    /// Code of the [`UiTheme`] of menus
+    pub theme: String,
+    pub accessibility: Accessibility,
+    pub control_scheme: ControlScheme,
+}
+
+/// Volumes in range 0.0..=1.0
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Volume {
+    pub master: f32,
+    pub music: f32,
+    pub sfx: f32,
+    pub ui: f32,
+}
+
+/// Options applied on top of the [`UiTheme`] and to the camera
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct Accessibility {
+    /// Multiplier of all sizes of menus and texts
+    pub ui_scale: f32,
+    /// Puts a solid panel behind menus and big texts
+    pub high_contrast: bool,
+    /// Automatic camera effects are turned off, the player's own camera control stays
+    pub reduced_motion: bool,
+    pub palette: SelectionPalette,
+}
+
+/// Colors of the focused control. Palettes other than the default one
+/// also mark the focused control with a bar, so selection doesn't depend on hue
+#[derive(Serialize, Deserialize, Debug, EnumIter, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SelectionPalette {
+    #[default]
+    Default,
+    Deuteranopia,
+    Protanopia,
+    Tritanopia,
+}
+
+/// How the player moves the claw
+#[derive(Serialize, Deserialize, Debug, EnumIter, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ControlScheme {
+    /// Along both axes at once, released with a button
+    #[default]
+    Free,
+    /// Along X while the input is held, then along Z, dropped as soon as it's let go
+    OneAxis,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            volume: default(),
+            msaa: 4,
+            fullscreen: false,
+            vsync: true,
+            show_fps: true,
+            camera_sensitivity: 1.0,
+            player_name: std::env::var("USER")
+                .or_else(|_| std::env::var("USERNAME"))
+                .unwrap_or_else(|_| "Player".to_string()),
+            language: "en".to_string(),
+            theme: UiTheme::DEFAULT.to_string(),
+            accessibility: default(),
+            control_scheme: default(),
+        }
+    }
+}
+
+impl Default for Accessibility {
+    fn default() -> Self {
+        Self {
+            ui_scale: 1.0,
+            high_contrast: false,
+            reduced_motion: false,
+            palette: SelectionPalette::Default,
+        }
+    }
+}
+
+impl SelectionPalette {
+    /// Localization key of the palette name
+    pub fn label(&self) -> &'static str {
+        match self {
+            SelectionPalette::Default => "palette.default",
+            SelectionPalette::Deuteranopia => "palette.deuteranopia",
+            SelectionPalette::Protanopia => "palette.protanopia",
+            SelectionPalette::Tritanopia => "palette.tritanopia",
+        }
+    }
+}
+
+impl ControlScheme {
+    /// Localization key of the scheme name
+    pub fn label(&self) -> &'static str {
+        match self {
+            ControlScheme::Free => "control_scheme.free",
+            ControlScheme::OneAxis => "control_scheme.one_axis",
+        }
+    }
+}
+
+impl Default for Volume {
+    fn default() -> Self {
+        Self { master: 1.0, music: 1.0, sfx: 1.0, ui: 1.0 }
+    }
+}
+
+impl Settings {
+    pub const FILE_NAME: &'static str = "settings.ron";
+    pub const MSAA_SAMPLES: [u32; 2] = [1, 4];
+    pub const PLAYER_NAME_LENGTH: usize = 12;
+    pub const UI_SCALE_RANGE: RangeInclusive<f32> = 0.75..=1.5;
+
+    pub fn load() -> Self {
+        let mut settings: Settings = storage::load(Settings::FILE_NAME).unwrap_or_default();
+
+        if !Settings::MSAA_SAMPLES.contains(&settings.msaa) {
+            settings.msaa = Settings::default().msaa;
+        }
+
+        if settings.player_name.trim().is_empty() {
+            settings.player_name = Settings::default().player_name;
+        }
+
+        settings.accessibility.ui_scale = settings.accessibility.ui_scale
+            .clamp(*Settings::UI_SCALE_RANGE.start(), *Settings::UI_SCALE_RANGE.end());
+
+        settings
+    }
+
+    pub fn save(&self) {
+        storage::save(Settings::FILE_NAME, self);
+    }
+
+    pub fn music_volume(&self) -> f32 {
+        self.volume.master * self.volume.music
+    }
+
+    pub fn sfx_volume(&self) -> f32 {
+        self.volume.master * self.volume.sfx
+    }
+
+    pub fn ui_volume(&self) -> f32 {
+        self.volume.master * self.volume.ui
+    }
+
+    fn window_mode(&self) -> WindowMode {
+        if self.fullscreen { WindowMode::BorderlessFullscreen } else { WindowMode::Windowed }
+    }
+
+    fn present_mode(&self) -> PresentMode {
+        if self.vsync { PresentMode::Fifo } else { PresentMode::Immediate }
+    }
+}
+
+fn apply_settings_system(
+    settings: Res<Settings>,
+    mut windows: ResMut<Windows>,
+    mut msaa: ResMut<Msaa>,
+    audio_background: Res<AudioChannel<BackgroundAudioChannel>>,
+    audio_glass: Res<AudioChannel<GlassAudioChannel>>,
+    audio_drop: Res<AudioChannel<DropAudioChannel>>,
+    audio_ui: Res<AudioChannel<UiAudioChannel>>,
+) {
+    if !settings.is_changed() { return; }
+
+    if let Some(window) = windows.get_primary_mut() {
+        if window.mode() != settings.window_mode() {
+            window.set_mode(settings.window_mode());
+        }
+
+        if window.present_mode() != settings.present_mode() {
+            window.set_present_mode(settings.present_mode());
+        }
+    }
+
+    if msaa.samples != settings.msaa {
+        msaa.samples = settings.msaa;
+    }
+
+    audio_background.set_volume(settings.music_volume());
+    audio_glass.set_volume(settings.sfx_volume());
+    audio_drop.set_volume(settings.sfx_volume() * DropAudioChannel::GAIN);
+    audio_ui.set_volume(settings.ui_volume());
+}
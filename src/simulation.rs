@@ -0,0 +1,304 @@
+use std::time::Duration;
+
+use bevy::{prelude::*, core::CorePlugin, transform::TransformPlugin, hierarchy::HierarchyPlugin, asset::AssetPlugin};
+use bevy_rapier3d::prelude::*;
+use iyes_loopless::prelude::*;
+
+use crate::{
+    GameState,
+    claw::{ClawPlugin, ClawController, ClawControllerState, ReleaseClawEvent, ToyCatchEvent, ClawReturnedToBaseEvent},
+    claw_machine::spawn_claw_machine,
+    clock::GameClock,
+    controls::{ClawInput, ClawInputOverride},
+    glue::GluePlugin,
+    movement::MovementPlugin,
+    rng::GameRng,
+    toy::spawn_toys,
+};
+
+/// Claw machine without window, rendering and audio.
+/// Steps the claw state machine and physics with a fixed time step
+/// and is driven by a script instead of devices
+pub struct Simulation {
+    app: App,
+    elapsed: f32,
+}
+
+/// Claw events fired since the simulation started
+#[derive(Default, Debug, Clone)]
+pub struct SimulationLog {
+    pub claw_released: u32,
+    pub toys_caught: u32,
+    pub claw_returned: u32,
+}
+
+impl Simulation {
+    pub const TIMESTEP: f32 = 1.0 / 60.0;
+
+    /// `seed` is a round seed, so the toy layout is the same
+    /// as in a replay with the same seed
+    pub fn new(seed: u64) -> Self {
+        let mut app = App::new();
+        let mut rng = GameRng::new(seed);
+
+        rng.start_round(Some(seed));
+
+        app
+            .add_plugin(CorePlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(HierarchyPlugin)
+            .add_plugin(AssetPlugin)
+            .add_asset::<Mesh>()
+            .add_asset::<StandardMaterial>()
+            .init_resource::<Input<KeyCode>>()
+            .insert_resource(GameClock::default())
+            .init_resource::<ClawInput>()
+            .insert_resource(ClawInputOverride)
+            .insert_resource(rng)
+            .init_resource::<SimulationLog>()
+
+            .add_loopless_state(GameState::InGame)
+
+            .add_plugin(MovementPlugin)
+            .add_plugin(ClawPlugin)
+            .add_plugin(GluePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default())
+
+            .add_startup_system(setup_system)
+            .add_system(log_system);
+
+        app.world
+            .resource_mut::<GameClock>()
+            .set_delta(Duration::from_secs_f32(Simulation::TIMESTEP));
+
+        Self { app, elapsed: 0.0 }
+    }
+
+    /// Advances the simulation by one [`Simulation::TIMESTEP`]
+    pub fn step(&mut self) {
+        self.app.update();
+        self.elapsed += Simulation::TIMESTEP;
+
+        self.app.world.resource_mut::<ClawInput>().release = false;
+    }
+
+    /// Simulated seconds since start
+    pub fn elapsed(&self) -> f32 {
+        self.elapsed
+    }
+
+    pub fn wait(&mut self, seconds: f32) {
+        let steps = (seconds / Simulation::TIMESTEP).ceil() as u32;
+
+        for _ in 0..steps {
+            self.step();
+        }
+    }
+
+    /// Holds the movement input for given seconds, then lets it go
+    pub fn hold(&mut self, x: f32, z: f32, seconds: f32) {
+        self.set_input(x, z);
+        self.wait(seconds);
+        self.set_input(0.0, 0.0);
+    }
+
+    pub fn set_input(&mut self, x: f32, z: f32) {
+        let mut input = self.app.world.resource_mut::<ClawInput>();
+
+        input.x = x;
+        input.z = z;
+    }
+
+    /// Presses release for a single step
+    pub fn release_claw(&mut self) {
+        self.app.world.resource_mut::<ClawInput>().release = true;
+        self.step();
+    }
+
+    /// Unlocks the claw the same way the countdown does at the start of a round
+    pub fn give_control(&mut self) {
+        let mut query = self.app.world.query::<&mut ClawController>();
+
+        for mut claw_controller in query.iter_mut(&mut self.app.world) {
+            claw_controller.0 = ClawControllerState::Manual;
+        }
+    }
+
+    /// Steps until `condition` is met. Returns `false` on timeout
+    pub fn run_until(&mut self, max_seconds: f32, condition: impl Fn(&Simulation) -> bool) -> bool {
+        let deadline = self.elapsed + max_seconds;
+
+        while self.elapsed < deadline {
+            if condition(self) { return true; }
+
+            self.step();
+        }
+
+        condition(self)
+    }
+
+    pub fn claw_position(&mut self) -> Option<Vec3> {
+        let mut query = self.app.world.query_filtered::<&Transform, With<ClawController>>();
+
+        query.iter(&self.app.world).next().map(|transform| transform.translation)
+    }
+
+    pub fn claw_state(&mut self) -> Option<ClawControllerState> {
+        let mut query = self.app.world.query::<&ClawController>();
+
+        query.iter(&self.app.world).next().map(|claw_controller| claw_controller.0)
+    }
+
+    pub fn log(&self) -> &SimulationLog {
+        self.app.world.resource::<SimulationLog>()
+    }
+}
+
+/// Plays a full attempt and checks that the claw goes down, comes back
+/// to the base and gets locked. Used by `--headless` command line flag
+pub fn run_self_check(seed: u64) -> Result<SimulationLog, String> {
+    let mut simulation = Simulation::new(seed);
+
+    // Let the toys fall and settle
+    simulation.wait(2.0);
+    simulation.give_control();
+    simulation.hold(-1.0, -1.0, 0.3);
+
+    let moved_from = simulation.claw_position().ok_or("Claw is not spawned")?;
+
+    if moved_from.distance(Vec3::from(ClawController::BASE_POS)) < 0.1 {
+        return Err("Claw doesn't move".to_string());
+    }
+
+    simulation.release_claw();
+
+    if !simulation.run_until(30.0, |simulation| simulation.log().claw_returned > 0) {
+        return Err(format!("Claw hasn't returned to the base: {:?}", simulation.claw_state()));
+    }
+
+    let position = simulation.claw_position().ok_or("Claw is despawned")?;
+
+    if position.distance(Vec3::from(ClawController::BASE_POS)) > 0.01 {
+        return Err(format!("Claw has returned to {} instead of the base", position));
+    }
+
+    if simulation.claw_state() != Some(ClawControllerState::Locked) {
+        return Err(format!("Claw is left in {:?} state", simulation.claw_state()));
+    }
+
+    Ok(simulation.log().clone())
+}
+
+fn setup_system(
+    mut rng: ResMut<GameRng>,
+    mut rapier_config: ResMut<RapierConfiguration>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut commands: Commands,
+) {
+    rapier_config.timestep_mode = TimestepMode::Fixed { dt: Simulation::TIMESTEP, substeps: 1 };
+
+    spawn_claw_machine(&mut commands, &mut meshes, &mut materials, None);
+    spawn_toys(&mut commands, rng.gameplay(), None);
+}
+
+fn log_system(
+    mut release_events: EventReader<ReleaseClawEvent>,
+    mut catch_events: EventReader<ToyCatchEvent>,
+    mut return_events: EventReader<ClawReturnedToBaseEvent>,
+    mut log: ResMut<SimulationLog>,
+) {
+    log.claw_released += release_events.iter().count() as u32;
+    log.toys_caught += catch_events.iter().count() as u32;
+    log.claw_returned += return_events.iter().count() as u32;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::toy::Toy;
+
+    const SEED: u64 = 42;
+
+    #[test]
+    fn drop_at_base_returns_to_base() {
+        let mut simulation = Simulation::new(SEED);
+
+        simulation.wait(2.0);
+        simulation.give_control();
+        simulation.release_claw();
+
+        assert!(simulation.run_until(30.0, |simulation| simulation.log().claw_returned > 0));
+        assert!(simulation.elapsed() < 2.0 + 30.0);
+        assert_eq!(simulation.log().claw_released, 1);
+        assert_eq!(simulation.log().claw_returned, 1);
+
+        let position = simulation.claw_position().expect("claw is spawned");
+
+        assert!(position.distance(Vec3::from(ClawController::BASE_POS)) < 0.01);
+        assert_eq!(simulation.claw_state(), Some(ClawControllerState::Locked));
+    }
+
+    #[test]
+    fn claw_is_locked_until_control_is_given() {
+        let mut simulation = Simulation::new(SEED);
+
+        simulation.wait(0.5);
+        simulation.hold(-1.0, -1.0, 0.3);
+        simulation.release_claw();
+        simulation.wait(2.0);
+
+        let position = simulation.claw_position().expect("claw is spawned");
+
+        assert!(position.distance(Vec3::from(ClawController::BASE_POS)) < 0.01);
+        assert_eq!(simulation.claw_state(), Some(ClawControllerState::Locked));
+        assert_eq!(simulation.log().claw_returned, 0);
+    }
+
+    #[test]
+    fn self_check_passes() {
+        let log = run_self_check(SEED).expect("self check passes");
+
+        assert_eq!(log.claw_released, 1);
+        assert_eq!(log.claw_returned, 1);
+    }
+
+    /// Claw translation and state after every step of a full attempt,
+    /// and translations of the toys at the end
+    fn trace(seed: u64) -> (Vec<(Option<Vec3>, Option<ClawControllerState>)>, Vec<Vec3>) {
+        let mut simulation = Simulation::new(seed);
+        let mut claw = Vec::new();
+
+        simulation.wait(2.0);
+        simulation.give_control();
+        simulation.set_input(-1.0, -1.0);
+
+        for _ in 0..20 {
+            simulation.step();
+            claw.push((simulation.claw_position(), simulation.claw_state()));
+        }
+
+        simulation.set_input(0.0, 0.0);
+        simulation.release_claw();
+
+        while simulation.log().claw_returned == 0 && simulation.elapsed() < 30.0 {
+            simulation.step();
+            claw.push((simulation.claw_position(), simulation.claw_state()));
+        }
+
+        let mut query = simulation.app.world.query_filtered::<&Transform, With<Toy>>();
+        let toys = query.iter(&simulation.app.world).map(|transform| transform.translation).collect();
+
+        (claw, toys)
+    }
+
+    #[test]
+    fn same_seed_gives_same_run() {
+        let (first_claw, first_toys) = trace(SEED);
+        let (second_claw, second_toys) = trace(SEED);
+
+        assert_eq!(first_claw, second_claw);
+        assert_eq!(first_toys, second_toys);
+        assert!(!first_toys.is_empty());
+    }
+}
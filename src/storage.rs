@@ -0,0 +1,57 @@
+use std::{fs, path::{Path, PathBuf}};
+
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+const APP_DIR: &str = "claw-machine";
+
+/// Path to a file within the user data directory of the game
+pub fn data_path(file_name: &str) -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(APP_DIR).join(file_name))
+}
+
+/// Reads a RON file from the user data directory.
+/// Missing or broken file is treated as absent
+pub fn load<T: DeserializeOwned>(file_name: &str) -> Option<T> {
+    load_from(&data_path(file_name)?)
+}
+
+/// Reads a RON file from arbitrary location
+pub fn load_from<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    let content = fs::read_to_string(path).ok()?;
+
+    match ron::from_str(&content) {
+        Ok(value) => Some(value),
+        Err(error) => {
+            warn!("Failed to parse {:?}: {}", path, error);
+
+            None
+        }
+    }
+}
+
+/// Writes a RON file to the user data directory
+pub fn save<T: Serialize>(file_name: &str, value: &T) {
+    let path = match data_path(file_name) {
+        Some(path) => path,
+        None => {
+            warn!("User data directory is not available, {} is not saved", file_name);
+
+            return;
+        }
+    };
+
+    let result = ron::ser::to_string_pretty(value, default())
+        .map_err(|error| error.to_string())
+        .and_then(|content| {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir).map_err(|error| error.to_string())?;
+            }
+
+            fs::write(&path, content).map_err(|error| error.to_string())
+        });
+
+    if let Err(error) = result {
+        warn!("Failed to save {:?}: {}", path, error);
+    }
+}
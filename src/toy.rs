@@ -1,12 +1,15 @@
-use std::f32::consts::PI;
+use std::f32::consts::TAU;
 
 use bevy::{prelude::*, gltf::Gltf};
 use bevy_rapier3d::prelude::*;
 use iyes_loopless::prelude::*;
+use rand::Rng;
 
 use crate::{
     assets::gltf::{GltfHandleStorage, GltfCollection},
-    constants::{COL_GROUP_ALL, COL_GROUP_CLAW, COL_GROUP_TOY_EJECTION_SHELV, COL_GROUP_EJECTED_TOY}, GameState, helpers::despawn_with
+    constants::{COL_GROUP_ALL, COL_GROUP_CLAW, COL_GROUP_TOY_EJECTION_SHELV, COL_GROUP_EJECTED_TOY}, GameState, helpers::despawn_with,
+    clock::GameClock,
+    rng::GameRng
 };
 
 #[derive(Default)]
@@ -15,9 +18,28 @@ pub struct ToyPlugin;
 pub struct Toy;
 #[derive(Component)]
 pub struct ToySensor;
+/// Toy that has been taken out of the glass by the claw,
+/// it's despawned when the timer finishes and the toy is gone down the prize chute
+#[derive(Component)]
+pub struct EjectedToy(Timer);
+/// Tickets the toy is worth in the arcade mode
+#[derive(Component)]
+pub struct ToyValue(pub u32);
 
 impl Toy {
     const MAX_SPEED: f32 = 2.0;
+    /// Toys inside the glass at the start of the round
+    const COUNT: usize = 15;
+}
+
+impl EjectedToy {
+    const DESPAWN_SECS: f32 = 3.0;
+}
+
+impl Default for EjectedToy {
+    fn default() -> Self {
+        Self(Timer::from_seconds(EjectedToy::DESPAWN_SECS, false))
+    }
 }
 
 impl Plugin for ToyPlugin {
@@ -26,6 +48,7 @@ impl Plugin for ToyPlugin {
             .add_event::<RespawnToysEvent>()
             .add_exit_system(GameState::Loading, spawn_toys_system)
             .add_system(toy_speed_control_system)
+            .add_system(despawn_ejected_toys_system)
             .add_system(despawn_with::<Toy>.run_on_event::<RespawnToysEvent>())
             .add_system(spawn_toys_system.run_on_event::<RespawnToysEvent>());
     }
@@ -41,46 +64,88 @@ fn toy_speed_control_system(mut query: Query<&mut Velocity, With<Toy>>) {
     }
 }
 
+fn despawn_ejected_toys_system(
+    clock: Res<GameClock>,
+    mut query: Query<(Entity, &mut EjectedToy)>,
+    mut commands: Commands,
+) {
+    for (entity, mut ejected) in query.iter_mut() {
+        if ejected.0.tick(clock.delta()).just_finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
 fn spawn_toys_system(
     assets: Res<Assets<Gltf>>,
     asset_storage: Res<GltfHandleStorage>,
+    mut rng: ResMut<GameRng>,
     mut commands: Commands
 ) {
     if let Some(gltf) = assets.get(asset_storage.0.get(&GltfCollection::HighLander).unwrap()) {
-        let size = (0.1, 0.40, 0.25); // true collision is (0.1, 0.44, 0.25)
-        let copies = 15;
-        let radius = 0.5;
-
-        for index in 1..copies + 1 {
-            let angle = 360.0 / index as f32 * 180.0 / PI;
-
-            commands.spawn()
-                .insert(Toy)
-                .insert(RigidBody::Dynamic)
-                .insert(Transform {
-                    translation: Vec3::new(radius * f32::sin(angle), 2.5, radius * f32::cos(angle)),
-                    rotation: Quat::from_rotation_z(angle),
-                    ..Default::default()
-                })
-                .insert(GlobalTransform::identity())
-                .insert(Collider::round_cuboid(size.0, size.1, size.2, 0.02))
-                .insert(CollisionGroups::new(
-                    COL_GROUP_ALL,
-                    COL_GROUP_ALL - COL_GROUP_CLAW - COL_GROUP_TOY_EJECTION_SHELV - COL_GROUP_EJECTED_TOY
-                ))
-                .insert(Velocity::default())
-                .with_children(|parent| {
-                    parent.spawn()
-                        .insert_bundle((Transform::from_xyz(0.0, -size.1, 0.0), GlobalTransform::identity()))
-                        .with_children(|parent| {
-                            parent.spawn_scene(gltf.scenes[0].clone());
-                        });
-                    
-                    parent.spawn()
-                        .insert(ToySensor)
-                        .insert(Collider::ball(0.2))
-                        .insert(Sensor(true));
-                });
+        spawn_toys(&mut commands, rng.gameplay(), Some(gltf.scenes[0].clone()));
+    }
+}
+
+/// Replaces toys taken out of the glass, so there are always as many to catch as at the start
+pub fn top_up_toys_system(
+    assets: Res<Assets<Gltf>>,
+    asset_storage: Res<GltfHandleStorage>,
+    mut rng: ResMut<GameRng>,
+    query: Query<(), (With<Toy>, Without<EjectedToy>)>,
+    mut commands: Commands
+) {
+    let missing = Toy::COUNT.saturating_sub(query.iter().count());
+
+    if missing == 0 { return; }
+
+    if let Some(gltf) = asset_storage.0.get(&GltfCollection::HighLander).and_then(|handle| assets.get(handle)) {
+        for _ in 0..missing {
+            spawn_toy(&mut commands, rng.gameplay(), Some(gltf.scenes[0].clone()));
         }
     }
-}
\ No newline at end of file
+}
+
+/// Spawns toys inside the glass at random places, so the layout depends only on `rng` state.
+/// Visual model is attached only if `scene` is given
+pub fn spawn_toys(commands: &mut Commands, rng: &mut impl Rng, scene: Option<Handle<Scene>>) {
+    for _ in 0..Toy::COUNT {
+        spawn_toy(commands, rng, scene.clone());
+    }
+}
+
+fn spawn_toy(commands: &mut Commands, rng: &mut impl Rng, scene: Option<Handle<Scene>>) {
+    let size = (0.1, 0.40, 0.25); // true collision is (0.1, 0.44, 0.25)
+    let radius = 0.5;
+    let angle = rng.gen_range(0.0..TAU);
+
+    commands.spawn()
+        .insert(Toy)
+        .insert(RigidBody::Dynamic)
+        .insert(Transform {
+            translation: Vec3::new(radius * f32::sin(angle), 2.5, radius * f32::cos(angle)),
+            rotation: Quat::from_rotation_z(angle),
+            ..Default::default()
+        })
+        .insert(GlobalTransform::identity())
+        .insert(Collider::round_cuboid(size.0, size.1, size.2, 0.02))
+        .insert(CollisionGroups::new(
+            COL_GROUP_ALL,
+            COL_GROUP_ALL - COL_GROUP_CLAW - COL_GROUP_TOY_EJECTION_SHELV - COL_GROUP_EJECTED_TOY
+        ))
+        .insert(Velocity::default())
+        .with_children(|parent| {
+            if let Some(scene) = &scene {
+                parent.spawn()
+                    .insert_bundle((Transform::from_xyz(0.0, -size.1, 0.0), GlobalTransform::identity()))
+                    .with_children(|parent| {
+                        parent.spawn_scene(scene.clone());
+                    });
+            }
+
+            parent.spawn()
+                .insert(ToySensor)
+                .insert(Collider::ball(0.2))
+                .insert(Sensor(true));
+        });
+}
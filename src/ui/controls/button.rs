@@ -1,95 +1,216 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, ui::FocusPolicy};
 use bevy_kira_audio::AudioChannel;
 
-use crate::{assets::audio::{UiAudioChannel, AudioHandleStorage, AudioCollection}, constants::PURPLE_COLOR};
+use crate::{
+    assets::{audio::{UiAudioChannel, AudioHandleStorage, AudioCollection}, theme::UiTheme},
+    localization::Localization,
+};
 
-use super::{Controls, SpawnedControl};
+use super::{SpawnedControl, focus::Disabled};
 
 #[derive(Component, Clone)]
 pub struct CMUIButton {
     pub key: String,
-    pub text: &'static str,
-    pub is_selected_by_default: bool
+    /// Localization key of the text
+    pub text: String,
+    pub is_selected_by_default: bool,
+    /// Greyed out, skipped by navigation and can't be pressed
+    pub is_disabled: bool,
+    /// Not spawned in the menu
+    pub is_hidden: bool,
+    /// Localization key of the line shown under the menu while the button is focused
+    pub description: Option<String>,
 }
 
 impl CMUIButton {
-    const COLOR_NORMAL: Color = Color::rgba(0.0, 0.0, 0.0, 0.0);
-    const COLOR_ACTIVE: Color = PURPLE_COLOR;
-    const ITERACTION_KEYS: [KeyCode; 3] = [
-        KeyCode::W,
-        KeyCode::S,
-        KeyCode::Return
-    ];
-
-    pub fn new<T: ToString>(key: T, text: &'static str) -> Self {
-        CMUIButton { key: key.to_string(), text, is_selected_by_default: false }
-    } 
+    pub fn new<T: ToString, S: ToString>(key: T, text: S) -> Self {
+        CMUIButton {
+            key: key.to_string(),
+            text: text.to_string(),
+            is_selected_by_default: false,
+            is_disabled: false,
+            is_hidden: false,
+            description: None,
+        }
+    }
 
     pub fn selected(mut self) -> Self {
         self.is_selected_by_default = true;
 
         self
     }
+
+    pub fn disabled(mut self, is_disabled: bool) -> Self {
+        self.is_disabled = is_disabled;
+
+        self
+    }
+
+    pub fn hidden(mut self, is_hidden: bool) -> Self {
+        self.is_hidden = is_hidden;
+
+        self
+    }
+
+    pub fn with_description<T: ToString>(mut self, description: T) -> Self {
+        self.description = Some(description.to_string());
+
+        self
+    }
 }
 
 #[derive(Default)]
 pub struct ButtonState {
-    selected: Option<Entity>,
-    prev_selected: Option<Entity>
+    pub(super) selected: Option<Entity>,
+    prev_selected: Option<Entity>,
+    /// Menu the focus is kept in, see [`menu_navigation_system`](super::focus::menu_navigation_system)
+    pub(super) menu: Option<Entity>,
+    /// Key of the focused control, restores the focus after the menu is rebuilt
+    pub(super) focused_key: Option<String>,
+    /// Button node of a disabled control under the mouse, it can't get the focus
+    /// but shows its description
+    pub(super) hovered_disabled: Option<Entity>,
+}
+
+impl ButtonState {
+    /// Button node of the focused control
+    pub fn selected(&self) -> Option<Entity> {
+        self.selected
+    }
+
+    /// Entity of the focused control, e.g. [`CMUIButton`]
+    pub fn focused_control(&self, nodes: &Query<&Parent, With<SelectedByDefault>>) -> Option<Entity> {
+        self.selected
+            .and_then(|node| nodes.get(node).ok())
+            .map(|parent| parent.0)
+    }
+
+    /// Entity of the control whose description is shown: the hovered disabled one, or the focused one
+    pub fn described_control(&self, nodes: &Query<&Parent, With<SelectedByDefault>>) -> Option<Entity> {
+        self.hovered_disabled
+            .and_then(|node| nodes.get(node).ok())
+            .map(|parent| parent.0)
+            .or_else(|| self.focused_control(nodes))
+    }
 }
 
 #[derive(Component)]
-pub struct SelectedByDefault(bool);
+pub struct SelectedByDefault(pub bool);
+
+/// Bar on the side of the focused button, shown by colorblind palettes
+#[derive(Component)]
+pub struct FocusMarker;
 
 pub struct ButtonPressEvent(pub String);
 
-pub fn button_spawner_system(
-    controls: Res<Controls>,
-    buttons: Query<(Entity, &CMUIButton), Without<SpawnedControl<CMUIButton>>>,
+/// Control of [`CMUIMenu`](super::menu::CMUIMenu) that can be focused with keyboard or gamepad.
+/// It's rendered as a button with a text and takes part in menu navigation
+pub trait FocusableControl: Component + Clone {
+    fn key(&self) -> &str;
+
+    /// Text shown on the control, with [`Localization`] applied
+    fn text(&self, localization: &Localization) -> String;
+
+    fn is_selected_by_default(&self) -> bool;
+
+    fn is_disabled(&self) -> bool {
+        false
+    }
+
+    fn description(&self) -> Option<&str> {
+        None
+    }
+
+    /// Spawns additional nodes inside the button
+    fn spawn_extra(&self, _button: &mut ChildBuilder, _theme: &UiTheme) {}
+}
+
+impl FocusableControl for CMUIButton {
+    fn key(&self) -> &str {
+        &self.key
+    }
+
+    fn text(&self, localization: &Localization) -> String {
+        localization.get(&self.text)
+    }
+
+    fn is_selected_by_default(&self) -> bool {
+        self.is_selected_by_default
+    }
+
+    fn is_disabled(&self) -> bool {
+        self.is_disabled
+    }
+
+    fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+}
+
+pub fn control_spawner_system<T: FocusableControl>(
+    theme: Res<UiTheme>,
+    localization: Res<Localization>,
+    query: Query<(Entity, &T), Without<SpawnedControl<T>>>,
     mut commands: Commands
 ) {
-    for (entity, component) in buttons.iter() {
+    for (entity, component) in query.iter() {
         commands.entity(entity)
-            .insert(SpawnedControl::<CMUIButton>::new())
+            .insert(SpawnedControl::<T>::new())
             .with_children(|button| {
                 button.spawn()
-                    .insert(SelectedByDefault(component.is_selected_by_default))
+                    .insert(SelectedByDefault(component.is_selected_by_default()))
                     .insert_bundle(ButtonBundle {
                         style: Style {
-                            size: Size::new(Val::Px(280.0), Val::Px(65.0)),
+                            size: Size::new(Val::Px(theme.button_width), Val::Px(theme.button_height)),
                             justify_content: JustifyContent::Center,
                             align_items: AlignItems::Center,
-                            margin: Rect { top: Val::Px(10.0), ..default() },
+                            margin: Rect { top: theme.px(10.0), ..default() },
                             ..default()
                         },
-                        color: if component.is_selected_by_default {
-                            CMUIButton::COLOR_ACTIVE.into()
+                        color: if component.is_selected_by_default() && !component.is_disabled() {
+                            theme.active_color.into()
                         } else {
-                            CMUIButton::COLOR_NORMAL.into()
+                            theme.normal_color.into()
                         },
                         ..default()
                     })
                     .with_children(|parent| {
+                        let is_marked = component.is_selected_by_default() && !component.is_disabled();
+
+                        parent.spawn()
+                            .insert(FocusMarker)
+                            .insert_bundle(NodeBundle {
+                                style: Style {
+                                    position_type: PositionType::Absolute,
+                                    position: Rect { top: Val::Px(0.0), left: Val::Px(0.0), ..default() },
+                                    size: Size::new(theme.px(UiTheme::MARKER_WIDTH), Val::Percent(100.0)),
+                                    ..default()
+                                },
+                                color: theme.marker_color.filter(|_| is_marked).unwrap_or(Color::NONE).into(),
+                                focus_policy: FocusPolicy::Pass,
+                                ..default()
+                            });
+
                         for index in 0..=1 {
                             parent.spawn_bundle(TextBundle {
                                 style: Style {
                                     position_type: PositionType::Absolute,
                                     position: Rect {
-                                        top: Val::Px(14.0 - (index * 2) as f32),
-                                        left: Val::Px(20.0 - (index * 2) as f32),
+                                        top: theme.px(14.0 - (index * 2) as f32),
+                                        left: theme.px(20.0 - (index * 2) as f32),
                                         ..default()
                                     },
                                     ..default()
                                 },
                                 text: Text::with_section(
-                                    component.text.clone(),
+                                    component.text(&localization),
                                     TextStyle {
-                                        font: controls.font.clone(),
-                                        font_size: 40.0,
-                                        color: if index == 0 {
-                                            PURPLE_COLOR
-                                        } else {
-                                            Color::ANTIQUE_WHITE
+                                        font: theme.font.clone(),
+                                        font_size: theme.button_font_size,
+                                        color: match (index, component.is_disabled()) {
+                                            (_, false) => theme.layer_color(index),
+                                            (0, true) => theme.disabled_shadow_color,
+                                            (_, true) => theme.disabled_text_color,
                                         },
                                     },
                                     default(),
@@ -97,18 +218,57 @@ pub fn button_spawner_system(
                                 ..default()
                             });
                         }
+
+                        component.spawn_extra(parent, &theme);
                     });
                 });
     }
 }
 
+/// Keeps the text of spawned controls in sync with [`FocusableControl::text`]
+/// and the current language
+pub fn control_text_system<T: FocusableControl>(
+    localization: Res<Localization>,
+    query: Query<(&T, ChangeTrackers<T>, &Children), With<SpawnedControl<T>>>,
+    children_query: Query<&Children>,
+    mut text_query: Query<&mut Text>,
+) {
+    for (component, tracker, children) in query.iter() {
+        if !tracker.is_changed() && !localization.is_changed() { continue; }
+
+        for child in children.iter() {
+            if let Ok(text_layers) = children_query.get(*child) {
+                for text_layer in text_layers.iter() {
+                    if let Ok(mut text) = text_query.get_mut(*text_layer) {
+                        text.sections[0].value = component.text(&localization);
+                    }
+                }
+            }
+        }
+    }
+}
+
 pub fn handle_interaction_system(
     mut event: EventWriter<ButtonPressEvent>,
     mut state: ResMut<ButtonState>,
     mut interaction_query: Query<(Entity, &Interaction, &Parent), Changed<Interaction>>,
     query_cmui_buttons: Query<&CMUIButton>,
+    disabled_query: Query<(), With<Disabled>>,
 ) {
     for (entity, interaction, parent) in interaction_query.iter_mut() {
+        // Disabled button only explains itself on hover
+        if disabled_query.get(parent.0).is_ok() {
+            if *interaction == Interaction::None {
+                if state.hovered_disabled == Some(entity) {
+                    state.hovered_disabled = None;
+                }
+            } else {
+                state.hovered_disabled = Some(entity);
+            }
+
+            continue;
+        }
+
         match interaction {
             Interaction::Clicked => {
                 state.selected = Some(entity);
@@ -129,15 +289,24 @@ pub fn handle_interaction_system(
 
 pub fn button_animation_system(
     state: Res<ButtonState>,
-    mut query: Query<(Entity, &mut UiColor)>,
+    theme: Res<UiTheme>,
+    mut query: Query<(Entity, &mut UiColor), With<SelectedByDefault>>,
+    mut markers: Query<(&Parent, &mut UiColor), (With<FocusMarker>, Without<SelectedByDefault>)>,
 ) {
     for (entity, mut color) in query.iter_mut() {
         if Some(entity) == state.selected {
-            *color = CMUIButton::COLOR_ACTIVE.into();
+            *color = theme.active_color.into();
         } else {
-            *color = CMUIButton::COLOR_NORMAL.into();
+            *color = theme.normal_color.into();
         }
     }
+
+    for (parent, mut color) in markers.iter_mut() {
+        *color = theme.marker_color
+            .filter(|_| Some(parent.0) == state.selected)
+            .unwrap_or(Color::NONE)
+            .into();
+    }
 }
 
 pub fn selected_button_changed(mut state: ResMut<ButtonState>) -> bool {
@@ -150,66 +319,6 @@ pub fn selected_button_changed(mut state: ResMut<ButtonState>) -> bool {
     statement
 }
 
-pub fn keyboard_button_interaction_system(
-    mut event: EventWriter<ButtonPressEvent>,
-    mut state: ResMut<ButtonState>,
-    keyboard: Res<Input<KeyCode>>,
-    query: Query<(Entity, &SelectedByDefault, &Parent)>,
-    query_cmui_buttons: Query<&CMUIButton>,
-) {
-    if keyboard.any_just_pressed(CMUIButton::ITERACTION_KEYS) {
-        let mut iter = query.iter();
-        let first_entity = iter.nth(0).unwrap();
-        let last_entity = iter.last().unwrap_or(first_entity).0;
-        let first_entity = first_entity.0;
-
-        for (index, (entity, selected_by_default, _)) in query.iter().enumerate() {
-            if keyboard.just_pressed(KeyCode::S) || keyboard.just_pressed(KeyCode::W) {
-                let is_next = keyboard.just_pressed(KeyCode::S);
-                let (next_index, overflow_button) = if is_next {
-                    (index + 1, first_entity)
-                } else {
-                    (
-                        if index > 0 { index - 1 } else { query.iter().size_hint().1.unwrap() - 1 },
-                        last_entity
-                    )
-                };
-
-                if Some(entity) == state.selected || (state.selected.is_none() && selected_by_default.0) {
-                    if let Some((next_entity, _, _)) = query.iter().nth(next_index) {
-                        state.selected = Some(next_entity);
-                    } else {
-                        state.selected = Some(overflow_button);
-                    };
-
-                    break;
-                }
-            }
-        }
-
-        if keyboard.just_pressed(KeyCode::Return) {
-            let (_, _, default) = query.iter()
-                .find(|(_, selected_by_default, _)| { selected_by_default.0 })
-                .expect("No selected by default button! You must specify selected button.");
-
-            let selected = state.selected
-                .and_then(|entity| {
-                    if let Ok(button) = query.get(entity) {
-                        Some(button)
-                    } else {
-                        None
-                    }
-                })
-                .and_then(|(_, _, selected)| Some(selected))
-                .unwrap_or(default);
-
-            if let Ok(cmui_button) = query_cmui_buttons.get(**selected) {
-                event.send(ButtonPressEvent(cmui_button.key.clone()));
-            }
-        }
-    }
-}
-
 pub fn button_sfx_system(
     mut events: EventReader<ButtonPressEvent>,
     audio: Res<AudioChannel<UiAudioChannel>>,
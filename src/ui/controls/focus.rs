@@ -0,0 +1,143 @@
+use bevy::prelude::*;
+
+use crate::controls::GameAction;
+
+use super::{
+    SpawnedControl,
+    ButtonPressEvent,
+    button::{ButtonState, CMUIButton, SelectedByDefault},
+    menu::{CMUIMenu, MenuControl},
+};
+
+/// Control is shown but can't be focused or pressed
+#[derive(Component)]
+pub struct Disabled;
+
+/// Focusable control of the active menu
+struct FocusTarget {
+    /// Button node that gets the focus
+    node: Entity,
+    control: Entity,
+    slot: usize,
+    is_default: bool,
+}
+
+/// Moves focus inside the active [`CMUIMenu`] with menu [`GameAction`]s and presses buttons.
+/// Menus with several columns are navigated in two dimensions, one column menus
+/// leave left and right to the focused control. Disabled controls are skipped
+pub fn menu_navigation_system(
+    mut events: EventWriter<ButtonPressEvent>,
+    mut state: ResMut<ButtonState>,
+    actions: Res<Input<GameAction>>,
+    menus: Query<(Entity, &CMUIMenu), With<SpawnedControl<CMUIMenu>>>,
+    nodes: Query<(Entity, &SelectedByDefault, &Parent)>,
+    controls: Query<&MenuControl, Without<Disabled>>,
+    buttons: Query<&CMUIButton>,
+) {
+    // Latest spawned menu is active, another one takes over when it's gone
+    let active = state.menu
+        .filter(|menu| menus.get(*menu).is_ok())
+        .or_else(|| menus.iter().next().map(|(entity, _)| entity));
+    let menu = match active.and_then(|active| menus.get(active).ok()) {
+        Some((_, menu)) => menu,
+        None => return,
+    };
+
+    let mut targets: Vec<FocusTarget> = nodes
+        .iter()
+        .filter_map(|(node, selected_by_default, parent)| {
+            controls
+                .get(parent.0)
+                .ok()
+                .filter(|control| Some(control.menu) == active)
+                .map(|control| FocusTarget {
+                    node,
+                    control: parent.0,
+                    slot: control.slot,
+                    is_default: selected_by_default.0,
+                })
+        })
+        .collect();
+
+    if targets.is_empty() { return; }
+
+    targets.sort_by_key(|target| target.slot);
+
+    // Focused control may be gone after the menu has been rebuilt, the one with its key takes over
+    let current = targets
+        .iter()
+        .position(|target| Some(target.node) == state.selected)
+        .or_else(|| {
+            let key = state.focused_key.as_ref()?;
+
+            targets.iter().position(|target| {
+                controls.get(target.control).map_or(false, |control| &control.key == key)
+            })
+        })
+        .or_else(|| targets.iter().position(|target| target.is_default))
+        .unwrap_or(0);
+
+    let dy = actions.just_pressed(GameAction::MenuDown) as isize - actions.just_pressed(GameAction::MenuUp) as isize;
+    let dx = if menu.columns > 1 {
+        actions.just_pressed(GameAction::MenuRight) as isize - actions.just_pressed(GameAction::MenuLeft) as isize
+    } else {
+        0
+    };
+
+    let next = if dx != 0 || dy != 0 {
+        // Description follows the keyboard again
+        state.hovered_disabled = None;
+
+        let count = menu.controls.iter().filter(|control| !control.is_hidden()).count();
+
+        navigate(&targets, current, menu.columns, count, dx, dy)
+    } else {
+        current
+    };
+
+    let target = &targets[next];
+
+    if state.selected != Some(target.node) {
+        state.selected = Some(target.node);
+    }
+
+    if let Ok(control) = controls.get(target.control) {
+        if state.focused_key.as_ref() != Some(&control.key) {
+            state.focused_key = Some(control.key.clone());
+        }
+    }
+
+    if actions.just_pressed(GameAction::Confirm) {
+        if let Ok(button) = buttons.get(target.control) {
+            events.send(ButtonPressEvent(button.key.clone()));
+        }
+    }
+}
+
+/// Position of the target the focus moves to. Steps over missing and disabled
+/// controls and wraps around the edges of the grid
+fn navigate(targets: &[FocusTarget], current: usize, columns: usize, count: usize, dx: isize, dy: isize) -> usize {
+    let columns = columns.max(1);
+    let count = count.max(targets[targets.len() - 1].slot + 1);
+    let rows = (count + columns - 1) / columns;
+    let row_length = |row: usize| columns.min(count - row * columns);
+
+    let slot = targets[current].slot;
+    let (row, column) = (slot / columns, slot % columns);
+
+    for step in 1..=count as isize {
+        let candidate = if dy != 0 {
+            let row = (row as isize + dy * step).rem_euclid(rows as isize) as usize;
+
+            row * columns + column.min(row_length(row) - 1)
+        } else {
+            row * columns + (column as isize + dx * step).rem_euclid(row_length(row) as isize) as usize
+        };
+
+        if let Some(position) = targets.iter().position(|target| target.slot == candidate) {
+            return position;
+        }
+    }
+
+    current
+}
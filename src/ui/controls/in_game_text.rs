@@ -1,14 +1,14 @@
 use bevy::prelude::*;
 
-use crate::constants::PURPLE_COLOR;
+use crate::assets::theme::UiTheme;
 
-use super::{Controls, SpawnedControl};
+use super::{SpawnedControl, spawn_backing_panel};
 
 #[derive(Component)]
 pub struct InGameText(pub String);
 
 pub fn in_game_text_spawner_system(
-    controls: Res<Controls>,
+    theme: Res<UiTheme>,
     texts: Query<(Entity, &InGameText), Without<Text>>,
     mut commands: Commands
 ) {
@@ -24,15 +24,18 @@ pub fn in_game_text_spawner_system(
                 ..default()
             })
             .with_children(|parent| {
+                spawn_backing_panel(parent, &theme);
+
                 for index in 0..=1 {
                     parent.spawn()
                         .insert(InGameText(text.0.clone()))
                         .insert_bundle(TextBundle {
                             style: Style {
-                                position_type: PositionType::Absolute,
+                                // Back layer takes space, so the backing panel gets the size of the text
+                                position_type: if index == 0 { PositionType::Relative } else { PositionType::Absolute },
                                 position: Rect {
-                                    top: Val::Px((-index * 4) as f32),
-                                    left: Val::Px((-index * 4) as f32),
+                                    top: theme.px((-index * 4) as f32),
+                                    left: theme.px((-index * 4) as f32),
                                     ..default()
                                 },
                                 ..default()
@@ -40,13 +43,9 @@ pub fn in_game_text_spawner_system(
                             text: Text::with_section(
                                 text.0.clone(),
                                 TextStyle {
-                                    font: controls.header_font.clone(),
-                                    font_size: 150.0,
-                                    color: if index == 0 {
-                                        PURPLE_COLOR
-                                    } else {
-                                        Color::ANTIQUE_WHITE
-                                    },
+                                    font: theme.header_font.clone(),
+                                    font_size: theme.in_game_font_size,
+                                    color: theme.layer_color(index),
                                 },
                                 default()
                             ),
@@ -56,3 +55,25 @@ pub fn in_game_text_spawner_system(
             });
     }
 }
+
+/// Applies the new [`UiTheme`] to the spawned texts
+pub fn in_game_text_theme_system(
+    theme: Res<UiTheme>,
+    texts: Query<&Children, With<SpawnedControl<InGameText>>>,
+    mut layers: Query<&mut Text, With<InGameText>>,
+) {
+    if !theme.is_changed() { return; }
+
+    for children in texts.iter() {
+        let mut index = 0;
+
+        for child in children.iter() {
+            if let Ok(mut text) = layers.get_mut(*child) {
+                text.sections[0].style.font = theme.header_font.clone();
+                text.sections[0].style.font_size = theme.in_game_font_size;
+                text.sections[0].style.color = theme.layer_color(index);
+                index += 1;
+            }
+        }
+    }
+}
@@ -1,21 +1,352 @@
 use bevy::prelude::*;
 
-use crate::constants::PURPLE_COLOR;
+use crate::{controls::GameAction, localization::Localization, assets::theme::UiTheme};
 
-use super::{Controls, SpawnedControl, button::CMUIButton};
+use super::{
+    SpawnedControl,
+    spawn_backing_panel,
+    ButtonPressEvent,
+    button::{ButtonState, CMUIButton, SelectedByDefault},
+    focus::Disabled,
+    slider::CMUISlider,
+    toggle::CMUIToggle,
+    select::CMUISelect,
+    text_input::CMUITextInput,
+};
 
-#[derive(Component)]
+/// Menu of controls laid out in rows of `columns` controls.
+/// Changed menu is spawned again, keeping the focused control
+#[derive(Component, Clone)]
 pub struct CMUIMenu {
+    /// Localization key of the title
     pub title: String,
-    pub buttons: Vec<CMUIButton>,
+    pub controls: Vec<CMUIMenuControl>,
+    pub columns: usize,
+}
+
+/// Spawned control of [`CMUIMenu`]
+#[derive(Component)]
+pub struct MenuControl {
+    pub menu: Entity,
+    /// Position in [`CMUIMenu::controls`]
+    pub index: usize,
+    /// Position in the grid of the menu, hidden controls don't take one
+    pub slot: usize,
+    pub key: String,
+}
+
+/// Line under the menu with the description of the focused control
+#[derive(Component)]
+pub struct MenuDescription(Entity);
+
+/// Text layer of the menu title
+#[derive(Component)]
+pub struct MenuTitle(Entity);
+
+/// Control listed in [`CMUIMenu`]
+#[derive(Clone)]
+pub enum CMUIMenuControl {
+    Button(CMUIButton),
+    Slider(CMUISlider),
+    Toggle(CMUIToggle),
+    Select(CMUISelect),
+    TextInput(CMUITextInput),
+}
+
+impl CMUIMenu {
+    /// Key of the button pressed by [`GameAction::Back`] in a menu without parents
+    pub const BACK_KEY: &'static str = "Back";
+    const DESCRIPTION_WIDTH: f32 = 500.0;
+
+    /// Menu of plain buttons
+    pub fn new<T: ToString>(title: T, buttons: Vec<CMUIButton>) -> Self {
+        CMUIMenu::with_controls(title, buttons.into_iter().map(CMUIMenuControl::from).collect())
+    }
+
+    pub fn with_controls<T: ToString>(title: T, controls: Vec<CMUIMenuControl>) -> Self {
+        CMUIMenu { title: title.to_string(), controls, columns: 1 }
+    }
+
+    /// Lays out controls in a grid navigated in two dimensions
+    pub fn columns(mut self, columns: usize) -> Self {
+        self.columns = columns.max(1);
+
+        self
+    }
+}
+
+impl CMUIMenuControl {
+    pub fn selected(mut self) -> Self {
+        self.set_selected(true);
+
+        self
+    }
+
+    fn key(&self) -> &str {
+        match self {
+            CMUIMenuControl::Button(button) => &button.key,
+            CMUIMenuControl::Slider(slider) => &slider.key,
+            CMUIMenuControl::Toggle(toggle) => &toggle.key,
+            CMUIMenuControl::Select(select) => &select.key,
+            CMUIMenuControl::TextInput(input) => &input.key,
+        }
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        matches!(self, CMUIMenuControl::Button(button) if button.is_hidden)
+    }
+
+    fn set_selected(&mut self, is_selected: bool) {
+        match self {
+            CMUIMenuControl::Button(button) => button.is_selected_by_default = is_selected,
+            CMUIMenuControl::Slider(slider) => slider.is_selected_by_default = is_selected,
+            CMUIMenuControl::Toggle(toggle) => toggle.is_selected_by_default = is_selected,
+            CMUIMenuControl::Select(select) => select.is_selected_by_default = is_selected,
+            CMUIMenuControl::TextInput(input) => input.is_selected_by_default = is_selected,
+        }
+    }
+}
+
+impl From<CMUIButton> for CMUIMenuControl {
+    fn from(button: CMUIButton) -> Self {
+        CMUIMenuControl::Button(button)
+    }
+}
+
+impl From<CMUISlider> for CMUIMenuControl {
+    fn from(slider: CMUISlider) -> Self {
+        CMUIMenuControl::Slider(slider)
+    }
+}
+
+impl From<CMUIToggle> for CMUIMenuControl {
+    fn from(toggle: CMUIToggle) -> Self {
+        CMUIMenuControl::Toggle(toggle)
+    }
+}
+
+impl From<CMUISelect> for CMUIMenuControl {
+    fn from(select: CMUISelect) -> Self {
+        CMUIMenuControl::Select(select)
+    }
+}
+
+impl From<CMUITextInput> for CMUIMenuControl {
+    fn from(input: CMUITextInput) -> Self {
+        CMUIMenuControl::TextInput(input)
+    }
+}
+
+/// Submenus opened on top of each other in the same [`CMUIMenu`] entity.
+/// Only the top one is shown, parents remember their focused control to restore it on return
+#[derive(Default)]
+pub struct MenuStack(Vec<StackedMenu>);
+
+struct StackedMenu {
+    /// Entity of the menu the parent was replaced in
+    root: Entity,
+    menu: CMUIMenu,
+    /// Index of the focused control
+    selected: Option<usize>,
+}
+
+/// Opens a submenu in place of the current [`CMUIMenu`] or returns to its parent
+pub enum MenuStackEvent {
+    Push(CMUIMenu),
+    Pop,
+}
+
+impl MenuStack {
+    /// Key of the button returning to the parent menu, see [`CMUIButton::back`]
+    pub const POP_KEY: &'static str = "MenuStackPop";
+
+    /// Number of parents of the shown menu
+    pub fn depth(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl CMUIButton {
+    /// Returns from a submenu to its parent
+    pub fn back() -> Self {
+        CMUIButton::new(MenuStack::POP_KEY, "ui.back")
+    }
+}
+
+pub fn menu_stack_system(
+    mut stack: ResMut<MenuStack>,
+    mut events: EventReader<MenuStackEvent>,
+    mut state: ResMut<ButtonState>,
+    parent_query: Query<&Parent>,
+    controls: Query<&MenuControl>,
+    mut menus: Query<(Entity, &mut CMUIMenu)>,
+) {
+    let current = menus.get_single().ok().map(|(root, _)| root);
+
+    // Parents left from menus that are gone
+    if stack.0.iter().any(|parent| Some(parent.root) != current) {
+        stack.0.retain(|parent| Some(parent.root) == current);
+    }
+
+    for event in events.iter() {
+        let (root, mut menu) = match menus.get_single_mut() {
+            Ok(menu) => menu,
+            Err(_) => continue,
+        };
+
+        match event {
+            MenuStackEvent::Push(submenu) => {
+                // Focused button node is a child of the control
+                let selected = state
+                    .selected()
+                    .and_then(|node| parent_query.get(node).ok())
+                    .and_then(|control| controls.get(control.0).ok())
+                    .map(|control| control.index);
+
+                stack.0.push(StackedMenu { root, menu: std::mem::replace(&mut *menu, submenu.clone()), selected });
+            }
+            MenuStackEvent::Pop => {
+                let parent = match stack.0.pop() {
+                    Some(parent) => parent,
+                    None => continue,
+                };
+
+                *menu = parent.menu;
+
+                if let Some(selected) = parent.selected {
+                    for (index, control) in menu.controls.iter_mut().enumerate() {
+                        control.set_selected(index == selected);
+                    }
+                }
+            }
+        }
+
+        // Focus goes to the control selected by default, which is the restored one for parents
+        *state = ButtonState::default();
+    }
+}
+
+/// Spawns changed menus again, see [`CMUIMenu`]
+pub fn menu_rebuild_system(
+    menus: Query<Entity, (Changed<CMUIMenu>, With<SpawnedControl<CMUIMenu>>)>,
+    mut commands: Commands,
+) {
+    for menu in menus.iter() {
+        commands.entity(menu)
+            .remove::<SpawnedControl<CMUIMenu>>()
+            .despawn_descendants();
+    }
+}
+
+/// Spawns menus again with the new [`UiTheme`].
+/// Values changed by the player are copied to the menu first, so they survive
+pub fn menu_theme_system(
+    theme: Res<UiTheme>,
+    mut menus: Query<&mut CMUIMenu, With<SpawnedControl<CMUIMenu>>>,
+    controls: Query<(&MenuControl, Option<&CMUISlider>, Option<&CMUIToggle>, Option<&CMUISelect>, Option<&CMUITextInput>)>,
+) {
+    if !theme.is_changed() { return; }
+
+    for mut menu in menus.iter_mut() {
+        menu.set_changed();
+    }
+
+    for (control, slider, toggle, select, input) in controls.iter() {
+        let current = slider
+            .map(|slider| CMUIMenuControl::from(slider.clone()))
+            .or_else(|| toggle.map(|toggle| toggle.clone().into()))
+            .or_else(|| select.map(|select| select.clone().into()))
+            .or_else(|| input.map(|input| input.clone().into()));
+
+        if let (Some(current), Ok(mut menu)) = (current, menus.get_mut(control.menu)) {
+            if let Some(slot) = menu.controls.get_mut(control.index) {
+                *slot = current;
+            }
+        }
+    }
+}
+
+/// [`GameAction::Back`] returns to the parent menu, or presses the
+/// [`CMUIMenu::BACK_KEY`] button of a menu without parents
+pub fn menu_back_system(
+    actions: Res<Input<GameAction>>,
+    stack: Res<MenuStack>,
+    menus: Query<(Entity, &CMUIMenu)>,
+    mut stack_events: EventWriter<MenuStackEvent>,
+    mut button_events: EventWriter<ButtonPressEvent>,
+) {
+    if !actions.just_pressed(GameAction::Back) { return; }
+
+    if let Ok((root, menu)) = menus.get_single() {
+        if stack.0.iter().any(|parent| parent.root == root) {
+            stack_events.send(MenuStackEvent::Pop);
+        } else if menu.controls.iter().any(|control| control.key() == CMUIMenu::BACK_KEY) {
+            button_events.send(ButtonPressEvent(CMUIMenu::BACK_KEY.to_string()));
+        }
+    }
+}
+
+/// Pressed [`CMUIButton::back`] returns to the parent menu
+pub fn menu_pop_button_system(
+    mut button_events: EventReader<ButtonPressEvent>,
+    mut stack_events: EventWriter<MenuStackEvent>,
+) {
+    for event in button_events.iter() {
+        if event.0 == MenuStack::POP_KEY {
+            stack_events.send(MenuStackEvent::Pop);
+        }
+    }
+}
+
+/// Shows [`CMUIButton::description`] of the focused button, or of the disabled one under the mouse
+pub fn menu_description_system(
+    state: Res<ButtonState>,
+    localization: Res<Localization>,
+    nodes: Query<&Parent, With<SelectedByDefault>>,
+    controls: Query<(&MenuControl, Option<&CMUIButton>)>,
+    mut descriptions: Query<(&MenuDescription, &mut Text)>,
+) {
+    if !state.is_changed() && !localization.is_changed() { return; }
+
+    let focused = state.described_control(&nodes).and_then(|control| controls.get(control).ok());
+
+    for (description, mut text) in descriptions.iter_mut() {
+        let value = focused
+            .filter(|(control, _)| control.menu == description.0)
+            .and_then(|(_, button)| button?.description.as_deref())
+            .map_or(String::new(), |description| localization.get(description));
+
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+}
+
+/// Translates titles of menus when the language is switched
+pub fn menu_title_system(
+    localization: Res<Localization>,
+    menus: Query<&CMUIMenu>,
+    mut titles: Query<(&MenuTitle, &mut Text)>,
+) {
+    if !localization.is_changed() { return; }
+
+    for (title, mut text) in titles.iter_mut() {
+        if let Ok(menu) = menus.get(title.0) {
+            text.sections[0].value = localization.get(&menu.title);
+        }
+    }
 }
 
 pub fn menu_spawner_system(
-    controls: Res<Controls>,
+    theme: Res<UiTheme>,
+    localization: Res<Localization>,
+    mut state: ResMut<ButtonState>,
     menus: Query<(Entity, &CMUIMenu), Without<SpawnedControl<CMUIMenu>>>,
     mut commands: Commands
 ) {
     for (entity, component) in menus.iter() {
+        state.menu = Some(entity);
+
         commands.entity(entity)
             .insert(SpawnedControl::<CMUIMenu>::new())
             .insert_bundle(TextBundle {
@@ -30,57 +361,110 @@ pub fn menu_spawner_system(
                 ..default()
             })
             .with_children(|menu| {
+                spawn_backing_panel(menu, &theme);
+
+                menu.spawn()
+                    .insert(MenuDescription(entity))
+                    .insert_bundle(TextBundle {
+                        style: Style {
+                            max_size: Size { width: theme.px(CMUIMenu::DESCRIPTION_WIDTH), height: Val::Undefined },
+                            margin: Rect { top: theme.px(30.0), ..default() },
+                            ..default()
+                        },
+                        text: Text::with_section(
+                            "",
+                            TextStyle {
+                                font: theme.font.clone(),
+                                font_size: theme.description_font_size,
+                                color: theme.text_color,
+                            },
+                            default()
+                        ),
+                        ..default()
+                    });
+
                 menu.spawn_bundle(TextBundle {
                     style: Style {
                         flex_direction: FlexDirection::ColumnReverse,
-                        margin: Rect { left: Val::Px(50.0), ..default() },
+                        margin: Rect { left: theme.px(50.0), ..default() },
                         ..default()
                     },
                     ..default()
                 })
                 .with_children(|parent| {
-                    component.buttons.iter().for_each(|button| {
-                        parent.spawn()
-                            .insert_bundle(TextBundle::default())
-                            .insert(button.clone());
-                    })
+                    let visible: Vec<(usize, &CMUIMenuControl)> = component.controls
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, control)| !control.is_hidden())
+                        .collect();
+                    let rows = visible.chunks(component.columns.max(1));
+
+                    for (row_index, row_controls) in rows.enumerate() {
+                        parent.spawn_bundle(TextBundle {
+                            style: Style { flex_direction: FlexDirection::Row, ..default() },
+                            ..default()
+                        })
+                        .with_children(|row| {
+                            for (column, (index, control)) in row_controls.iter().enumerate() {
+                                let mut control_entity = row.spawn();
+
+                                control_entity
+                                    .insert_bundle(TextBundle::default())
+                                    .insert(MenuControl {
+                                        menu: entity,
+                                        index: *index,
+                                        slot: row_index * component.columns.max(1) + column,
+                                        key: control.key().to_string(),
+                                    });
+
+                                match control {
+                                    CMUIMenuControl::Button(button) if button.is_disabled => {
+                                        control_entity.insert(button.clone()).insert(Disabled)
+                                    }
+                                    CMUIMenuControl::Button(button) => control_entity.insert(button.clone()),
+                                    CMUIMenuControl::Slider(slider) => control_entity.insert(slider.clone()),
+                                    CMUIMenuControl::Toggle(toggle) => control_entity.insert(toggle.clone()),
+                                    CMUIMenuControl::Select(select) => control_entity.insert(select.clone()),
+                                    CMUIMenuControl::TextInput(input) => control_entity.insert(input.clone()),
+                                };
+                            }
+                        });
+                    }
                 });
     
                 menu.spawn_bundle(TextBundle {
                     style: Style {
-                        size: Size { width: Val::Px(250.0), height: Val::Px(65.0) },
-                        margin: Rect { bottom: Val::Px(100.0), ..default() },
+                        size: Size { width: theme.px(250.0), height: theme.px(65.0) },
+                        margin: Rect { bottom: theme.px(100.0), ..default() },
                         ..default()
                     },
                     ..default()
                 })
                     .with_children(|parent| {
                         for index in 0..=1 {
-                            parent.spawn_bundle(TextBundle {
-                                style: Style {
-                                    position_type: PositionType::Absolute,
-                                    position: Rect {
-                                        top: Val::Px((-index * 4) as f32),
-                                        left: Val::Px((-index * 4) as f32),
+                            parent.spawn()
+                                .insert(MenuTitle(entity))
+                                .insert_bundle(TextBundle {
+                                    style: Style {
+                                        position_type: PositionType::Absolute,
+                                        position: Rect {
+                                            top: theme.px((-index * 4) as f32),
+                                            left: theme.px((-index * 4) as f32),
+                                            ..default()
+                                        },
                                         ..default()
                                     },
-                                    ..default()
-                                },
-                                text: Text::with_section(
-                                    component.title.to_string(),
-                                    TextStyle {
-                                        font: controls.header_font.clone(),
-                                        font_size: 100.0,
-                                        color: if index == 0 {
-                                            PURPLE_COLOR
-                                        } else {
-                                            Color::ANTIQUE_WHITE
+                                    text: Text::with_section(
+                                        localization.get(&component.title),
+                                        TextStyle {
+                                            font: theme.header_font.clone(),
+                                            font_size: theme.title_font_size,
+                                            color: theme.layer_color(index),
                                         },
-                                    },
-                                    default()
-                                ),
-                                ..default()
-                            });
+                                        default()
+                                    ),
+                                    ..default()
+                                });
                         }
                     });
             });
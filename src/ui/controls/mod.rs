@@ -1,16 +1,47 @@
 use std::marker::PhantomData;
 
-use bevy::prelude::*;
+use bevy::{prelude::*, ui::FocusPolicy};
 use iyes_loopless::prelude::*;
 
+use crate::{controls::ActionSystem, settings::Settings, assets::theme::UiTheme};
+
 use self::{
     button::*,
-    menu::menu_spawner_system, in_game_text::in_game_text_spawner_system
+    slider::*,
+    toggle::*,
+    select::*,
+    text_input::*,
+    focus::menu_navigation_system,
+    menu::{
+        CMUIMenu,
+        MenuStack,
+        MenuStackEvent,
+        menu_spawner_system,
+        menu_description_system,
+        menu_title_system,
+        menu_rebuild_system,
+        menu_stack_system,
+        menu_back_system,
+        menu_pop_button_system,
+        menu_theme_system,
+    },
+    in_game_text::{in_game_text_spawner_system, in_game_text_theme_system},
 };
 
-pub use self::button::ButtonPressEvent;
+pub use self::{
+    button::ButtonPressEvent,
+    slider::SliderChangedEvent,
+    toggle::ToggleChangedEvent,
+    select::SelectChangedEvent,
+    text_input::TextInputConfirmEvent,
+};
 
 pub mod button;
+pub mod focus;
+pub mod slider;
+pub mod toggle;
+pub mod select;
+pub mod text_input;
 pub mod menu;
 pub mod in_game_text;
 
@@ -20,37 +51,100 @@ pub struct ControlsPlugin;
 impl Plugin for ControlsPlugin {
     fn build(&self, app: &mut App) {
         app
-            .init_resource::<Controls>()
+            .init_resource::<UiTheme>()
             .init_resource::<ButtonState>()
+            .init_resource::<MenuStack>()
+            .add_event::<MenuStackEvent>()
             .add_event::<ButtonPressEvent>()
+            .add_event::<SliderChangedEvent>()
+            .add_event::<ToggleChangedEvent>()
+            .add_event::<SelectChangedEvent>()
+            .add_event::<TextInputConfirmEvent>()
             .add_system_set(
                 ConditionSet::new()
                     .run_if(control_type_exist::<CMUIButton>)
                     .with_system(button_animation_system.run_if(selected_button_changed))
-                    .with_system(handle_interaction_system)
-                    .with_system(keyboard_button_interaction_system)
                     .with_system(button_sfx_system)
                     .into()
             )
-            .add_system(button_spawner_system)
+            .add_system_set(
+                ConditionSet::new()
+                    .run_if(control_type_exist::<CMUIMenu>)
+                    .with_system(handle_interaction_system)
+                    .with_system(menu_navigation_system)
+                    .into()
+            )
+            .add_system_set(
+                ConditionSet::new()
+                    .run_if(control_type_exist::<CMUISlider>)
+                    .with_system(slider_input_system)
+                    .with_system(slider_drag_system)
+                    .with_system(slider_fill_system)
+                    .into()
+            )
+            .add_system(toggle_input_system.run_if(control_type_exist::<CMUIToggle>))
+            .add_system(select_input_system.run_if(control_type_exist::<CMUISelect>))
+            .add_system_set(
+                ConditionSet::new()
+                    .run_if(control_type_exist::<CMUITextInput>)
+                    .with_system(text_input_focus_system)
+                    .with_system(character_grid_system)
+                    .with_system(character_grid_highlight_system)
+                    .into()
+            )
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                text_input_editing_system.run_if(control_type_exist::<CMUITextInput>).after(ActionSystem)
+            )
+            .add_system(control_spawner_system::<CMUIButton>)
+            .add_system(control_spawner_system::<CMUISlider>)
+            .add_system(control_spawner_system::<CMUIToggle>)
+            .add_system(control_spawner_system::<CMUISelect>)
+            .add_system(control_spawner_system::<CMUITextInput>)
+            .add_system(control_text_system::<CMUIButton>)
+            .add_system(control_text_system::<CMUISlider>)
+            .add_system(control_text_system::<CMUIToggle>)
+            .add_system(control_text_system::<CMUISelect>)
+            .add_system(control_text_system::<CMUITextInput>)
             .add_system(clear_button_state.run_if_not(control_type_exist::<CMUIButton>))
+            .add_system(menu_back_system)
+            .add_system(menu_pop_button_system)
+            .add_system(menu_stack_system)
+            .add_system(menu_rebuild_system)
+            .add_system(menu_description_system)
+            .add_system(menu_title_system)
             .add_system(menu_spawner_system)
-            .add_system(in_game_text_spawner_system);
+            .add_system(in_game_text_spawner_system)
+            .add_system(update_theme_system)
+            .add_system(menu_theme_system)
+            .add_system(in_game_text_theme_system)
+            .add_system(backing_panel_theme_system);
     }
 }
 
-pub struct Controls {
-    pub font: Handle<Font>,
-    pub header_font: Handle<Font>,
-}
+/// Keeps the [`UiTheme`] resource equal to the theme picked in settings with accessibility options applied.
+/// Falls back to the default one, the resource is also updated when the file is edited
+fn update_theme_system(
+    settings: Res<Settings>,
+    themes: Res<Assets<UiTheme>>,
+    mut events: EventReader<AssetEvent<UiTheme>>,
+    mut theme: ResMut<UiTheme>,
+) {
+    let is_reloaded = events.iter().count() > 0;
 
-impl FromWorld for Controls {
-    fn from_world(world: &mut World) -> Self {
-        let asset_server = world.get_resource_mut::<AssetServer>().unwrap();
+    let is_adjusted = settings.accessibility == theme.accessibility;
 
-        Self {
-            font: asset_server.load("fonts/RussoOne-Regular.ttf"),
-            header_font: asset_server.load("fonts/Blaka-Regular.ttf")
+    if !is_reloaded && settings.theme == theme.code && is_adjusted { return; }
+
+    let picked = themes
+        .iter()
+        .map(|(_, theme)| theme)
+        .find(|theme| theme.code == settings.theme)
+        .or_else(|| themes.iter().map(|(_, theme)| theme).find(|theme| theme.code == UiTheme::DEFAULT));
+
+    if let Some(picked) = picked {
+        if is_reloaded || picked.code != theme.code || !is_adjusted {
+            *theme = picked.clone().with_accessibility(&settings.accessibility);
         }
     }
 }
@@ -68,4 +162,33 @@ impl<T> SpawnedControl<T> {
 
 pub fn control_type_exist<T: Component>(query: Query<With<SpawnedControl<T>>>) -> bool {
     !query.is_empty()
+}
+
+/// Solid panel behind the content of a node, visible when [`UiTheme::panel_color`] is set
+#[derive(Component)]
+pub struct BackingPanel;
+
+/// Spawns [`BackingPanel`] filling the parent. Must be the first child to be drawn below the others
+pub fn spawn_backing_panel(parent: &mut ChildBuilder, theme: &UiTheme) {
+    parent.spawn()
+        .insert(BackingPanel)
+        .insert_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect { top: Val::Px(0.0), left: Val::Px(0.0), ..default() },
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                ..default()
+            },
+            color: theme.panel_color.unwrap_or(Color::NONE).into(),
+            focus_policy: FocusPolicy::Pass,
+            ..default()
+        });
+}
+
+fn backing_panel_theme_system(theme: Res<UiTheme>, mut panels: Query<&mut UiColor, With<BackingPanel>>) {
+    if !theme.is_changed() { return; }
+
+    for mut color in panels.iter_mut() {
+        *color = theme.panel_color.unwrap_or(Color::NONE).into();
+    }
 }
\ No newline at end of file
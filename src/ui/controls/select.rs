@@ -0,0 +1,96 @@
+use bevy::prelude::*;
+
+use crate::{controls::GameAction, localization::Localization};
+
+use super::button::{ButtonState, FocusableControl, SelectedByDefault};
+
+/// One of several options. Cycled with left/right menu actions,
+/// confirm and mouse click move to the next option
+#[derive(Component, Clone)]
+pub struct CMUISelect {
+    pub key: String,
+    pub text: String,
+    pub options: Vec<String>,
+    pub index: usize,
+    pub is_selected_by_default: bool,
+}
+
+/// Option of [`CMUISelect`] with the key has been changed by the player
+pub struct SelectChangedEvent {
+    pub key: String,
+    pub index: usize,
+    pub value: String,
+}
+
+impl CMUISelect {
+    pub fn new<T: ToString, S: ToString, O: ToString>(key: T, text: S, options: &[O], index: usize) -> Self {
+        CMUISelect {
+            key: key.to_string(),
+            text: text.to_string(),
+            options: options.iter().map(ToString::to_string).collect(),
+            index,
+            is_selected_by_default: false,
+        }
+    }
+
+    pub fn selected(mut self) -> Self {
+        self.is_selected_by_default = true;
+
+        self
+    }
+
+    pub fn value(&self) -> Option<&str> {
+        self.options.get(self.index).map(String::as_str)
+    }
+}
+
+impl FocusableControl for CMUISelect {
+    fn key(&self) -> &str {
+        &self.key
+    }
+
+    fn text(&self, localization: &Localization) -> String {
+        format!("{}: < {} >", localization.get(&self.text), localization.get(self.value().unwrap_or_default()))
+    }
+
+    fn is_selected_by_default(&self) -> bool {
+        self.is_selected_by_default
+    }
+}
+
+pub fn select_input_system(
+    actions: Res<Input<GameAction>>,
+    state: Res<ButtonState>,
+    nodes: Query<&Parent, With<SelectedByDefault>>,
+    interaction_query: Query<(&Interaction, &Parent), Changed<Interaction>>,
+    mut selects: Query<&mut CMUISelect>,
+    mut events: EventWriter<SelectChangedEvent>,
+) {
+    let mut moves: Vec<(Entity, bool)> = interaction_query
+        .iter()
+        .filter(|(interaction, _)| **interaction == Interaction::Clicked)
+        .map(|(_, parent)| (parent.0, true))
+        .collect();
+
+    if let Some(entity) = state.focused_control(&nodes) {
+        if actions.any_just_pressed([GameAction::Confirm, GameAction::MenuRight]) {
+            moves.push((entity, true));
+        } else if actions.just_pressed(GameAction::MenuLeft) {
+            moves.push((entity, false));
+        }
+    }
+
+    for (entity, is_next) in moves {
+        if let Ok(mut select) = selects.get_mut(entity) {
+            let count = select.options.len();
+
+            if count < 2 { continue; }
+
+            select.index = if is_next { (select.index + 1) % count } else { (select.index + count - 1) % count };
+
+            if let Some(value) = select.value().map(str::to_string) {
+                events.send(SelectChangedEvent { key: select.key.clone(), index: select.index, value });
+            }
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use std::ops::RangeInclusive;
+
+use bevy::prelude::*;
+
+use crate::{controls::GameAction, localization::Localization, assets::theme::UiTheme};
+
+use super::button::{ButtonState, FocusableControl, SelectedByDefault};
+
+/// Numeric value in a range. Changed with left/right menu actions or by dragging with mouse
+#[derive(Component, Clone)]
+pub struct CMUISlider {
+    pub key: String,
+    pub text: String,
+    pub value: f32,
+    pub min: f32,
+    pub max: f32,
+    pub step: f32,
+    /// Formats the value next to the text
+    pub format: fn(f32) -> String,
+    pub is_selected_by_default: bool,
+}
+
+/// Value of [`CMUISlider`] with the key has been changed by the player
+pub struct SliderChangedEvent {
+    pub key: String,
+    pub value: f32,
+}
+
+#[derive(Component)]
+pub struct SliderFill;
+
+impl CMUISlider {
+    const FILL_HEIGHT: f32 = 4.0;
+
+    pub fn new<T: ToString, S: ToString>(key: T, text: S, value: f32, range: RangeInclusive<f32>, step: f32) -> Self {
+        CMUISlider {
+            key: key.to_string(),
+            text: text.to_string(),
+            value,
+            min: *range.start(),
+            max: *range.end(),
+            step,
+            format: |value| format!("{:.1}", value),
+            is_selected_by_default: false,
+        }
+    }
+
+    /// Shows the value in percents, for sliders in range `0.0..=1.0`
+    pub fn percent(mut self) -> Self {
+        self.format = |value| format!("{:.0}%", value * 100.0);
+
+        self
+    }
+
+    pub fn with_format(mut self, format: fn(f32) -> String) -> Self {
+        self.format = format;
+
+        self
+    }
+
+    pub fn selected(mut self) -> Self {
+        self.is_selected_by_default = true;
+
+        self
+    }
+
+    /// Rounds the value to the nearest step within the range
+    fn snap(&self, value: f32) -> f32 {
+        let steps = ((value - self.min) / self.step).round();
+
+        (self.min + steps * self.step).clamp(self.min, self.max)
+    }
+
+    fn fraction(&self) -> f32 {
+        if self.max > self.min {
+            (self.value - self.min) / (self.max - self.min)
+        } else {
+            0.0
+        }
+    }
+}
+
+impl FocusableControl for CMUISlider {
+    fn key(&self) -> &str {
+        &self.key
+    }
+
+    fn text(&self, localization: &Localization) -> String {
+        format!("{}: {}", localization.get(&self.text), (self.format)(self.value))
+    }
+
+    fn is_selected_by_default(&self) -> bool {
+        self.is_selected_by_default
+    }
+
+    fn spawn_extra(&self, button: &mut ChildBuilder, theme: &UiTheme) {
+        button.spawn()
+            .insert(SliderFill)
+            .insert_bundle(NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: Rect { bottom: Val::Px(0.0), left: Val::Px(0.0), ..default() },
+                    size: Size::new(Val::Percent(self.fraction() * 100.0), theme.px(CMUISlider::FILL_HEIGHT)),
+                    ..default()
+                },
+                color: theme.text_color.into(),
+                ..default()
+            });
+    }
+}
+
+pub fn slider_input_system(
+    actions: Res<Input<GameAction>>,
+    state: Res<ButtonState>,
+    nodes: Query<&Parent, With<SelectedByDefault>>,
+    mut sliders: Query<&mut CMUISlider>,
+    mut events: EventWriter<SliderChangedEvent>,
+) {
+    let direction = actions.just_pressed(GameAction::MenuRight) as i8 - actions.just_pressed(GameAction::MenuLeft) as i8;
+
+    if direction == 0 { return; }
+
+    if let Some(Ok(mut slider)) = state.focused_control(&nodes).map(|entity| sliders.get_mut(entity)) {
+        let value = slider.snap(slider.value + slider.step * direction as f32);
+
+        if value != slider.value {
+            slider.value = value;
+            events.send(SliderChangedEvent { key: slider.key.clone(), value });
+        }
+    }
+}
+
+pub fn slider_drag_system(
+    windows: Res<Windows>,
+    nodes: Query<(&Interaction, &Node, &GlobalTransform, &Parent)>,
+    mut sliders: Query<&mut CMUISlider>,
+    mut events: EventWriter<SliderChangedEvent>,
+) {
+    let cursor = match windows.get_primary().and_then(|window| window.cursor_position()) {
+        Some(cursor) => cursor,
+        None => return,
+    };
+
+    for (interaction, node, transform, parent) in nodes.iter() {
+        if *interaction != Interaction::Clicked || node.size.x <= 0.0 { continue; }
+
+        if let Ok(mut slider) = sliders.get_mut(parent.0) {
+            let left = transform.translation.x - node.size.x / 2.0;
+            let fraction = ((cursor.x - left) / node.size.x).clamp(0.0, 1.0);
+            let value = slider.snap(slider.min + fraction * (slider.max - slider.min));
+
+            if value != slider.value {
+                slider.value = value;
+                events.send(SliderChangedEvent { key: slider.key.clone(), value });
+            }
+        }
+    }
+}
+
+pub fn slider_fill_system(
+    sliders: Query<(&CMUISlider, &Children), Changed<CMUISlider>>,
+    children_query: Query<&Children>,
+    mut fills: Query<&mut Style, With<SliderFill>>,
+) {
+    for (slider, children) in sliders.iter() {
+        for child in children.iter() {
+            if let Ok(nodes) = children_query.get(*child) {
+                for node in nodes.iter() {
+                    if let Ok(mut style) = fills.get_mut(*node) {
+                        style.size.width = Val::Percent(slider.fraction() * 100.0);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,397 @@
+use bevy::prelude::*;
+use strum::IntoEnumIterator;
+
+use crate::{controls::{ActiveGamepad, GameAction}, localization::Localization, assets::theme::UiTheme};
+
+use super::{button::{ButtonState, FocusableControl, SelectedByDefault}};
+
+/// Single line text entry. Confirm or click starts editing, then typed characters
+/// are inserted at the caret until `Return` confirms or `Escape` cancels.
+/// While editing [`CharacterGrid`] is shown so the text can be entered with a gamepad
+#[derive(Component, Clone)]
+pub struct CMUITextInput {
+    pub key: String,
+    pub text: String,
+    pub value: String,
+    pub max_length: usize,
+    pub is_selected_by_default: bool,
+    is_editing: bool,
+    /// Position in characters
+    caret: usize,
+    /// Value before editing, restored when editing is cancelled
+    previous: String,
+}
+
+/// Value of [`CMUITextInput`] with the key has been confirmed by the player
+pub struct TextInputConfirmEvent {
+    pub key: String,
+    pub value: String,
+}
+
+impl CMUITextInput {
+    const CARET: char = '|';
+
+    pub fn new<T: ToString, S: ToString, V: ToString>(key: T, text: S, value: V, max_length: usize) -> Self {
+        let value: String = value.to_string().chars().take(max_length).collect();
+
+        CMUITextInput {
+            key: key.to_string(),
+            text: text.to_string(),
+            caret: value.chars().count(),
+            previous: value.clone(),
+            value,
+            max_length,
+            is_selected_by_default: false,
+            is_editing: false,
+        }
+    }
+
+    pub fn selected(mut self) -> Self {
+        self.is_selected_by_default = true;
+
+        self
+    }
+
+    fn start_editing(&mut self) {
+        self.is_editing = true;
+        self.caret = self.value.chars().count();
+        self.previous = self.value.clone();
+    }
+
+    fn confirm(&mut self) -> TextInputConfirmEvent {
+        self.is_editing = false;
+
+        TextInputConfirmEvent { key: self.key.clone(), value: self.value.clone() }
+    }
+
+    fn cancel(&mut self) {
+        self.is_editing = false;
+        self.value = self.previous.clone();
+    }
+
+    /// Byte offset of the caret in the value
+    fn caret_offset(&self) -> usize {
+        self.value
+            .char_indices()
+            .nth(self.caret)
+            .map_or(self.value.len(), |(offset, _)| offset)
+    }
+
+    fn insert(&mut self, character: char) {
+        if character.is_control() || self.value.chars().count() >= self.max_length { return; }
+
+        let offset = self.caret_offset();
+
+        self.value.insert(offset, character);
+        self.caret += 1;
+    }
+
+    fn backspace(&mut self) {
+        if self.caret == 0 { return; }
+
+        self.caret -= 1;
+
+        let offset = self.caret_offset();
+
+        self.value.remove(offset);
+    }
+
+    fn delete(&mut self) {
+        if self.caret < self.value.chars().count() {
+            let offset = self.caret_offset();
+
+            self.value.remove(offset);
+        }
+    }
+
+    fn move_caret(&mut self, caret: usize) {
+        self.caret = caret.min(self.value.chars().count());
+    }
+}
+
+impl FocusableControl for CMUITextInput {
+    fn key(&self) -> &str {
+        &self.key
+    }
+
+    fn text(&self, localization: &Localization) -> String {
+        let mut value = self.value.clone();
+
+        if self.is_editing {
+            value.insert(self.caret_offset(), CMUITextInput::CARET);
+        }
+
+        format!("{}: {}", localization.get(&self.text), value)
+    }
+
+    fn is_selected_by_default(&self) -> bool {
+        self.is_selected_by_default
+    }
+}
+
+/// On-screen keyboard for gamepads. `DPad` moves between cells, `South` presses the cell,
+/// `West` erases a character, `Start` confirms and `East` cancels
+#[derive(Component)]
+pub struct CharacterGrid {
+    index: usize,
+}
+
+#[derive(Component)]
+pub struct CharacterGridCell(usize);
+
+#[derive(Clone, Copy)]
+enum GridCell {
+    Character(char),
+    Delete,
+    Confirm,
+}
+
+impl CharacterGrid {
+    const CHARACTERS: &'static str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789-_. ";
+    const COLUMNS: usize = 10;
+    const CELL_SIZE: f32 = 50.0;
+
+    fn cells() -> Vec<GridCell> {
+        CharacterGrid::CHARACTERS
+            .chars()
+            .map(GridCell::Character)
+            .chain([GridCell::Delete, GridCell::Confirm])
+            .collect()
+    }
+
+    fn navigate(&mut self, dx: isize, dy: isize) {
+        let count = CharacterGrid::cells().len();
+        let columns = CharacterGrid::COLUMNS;
+
+        if dx != 0 {
+            self.index = (self.index as isize + dx).rem_euclid(count as isize) as usize;
+        }
+
+        if dy > 0 {
+            self.index = if self.index + columns < count { self.index + columns } else { self.index % columns };
+        } else if dy < 0 {
+            self.index = if self.index >= columns {
+                self.index - columns
+            } else {
+                let last_row = (count - 1) / columns * columns;
+
+                (last_row + self.index).min(count - 1)
+            };
+        }
+    }
+}
+
+impl GridCell {
+    fn label(&self) -> String {
+        match self {
+            GridCell::Character(' ') => "SPC".to_string(),
+            GridCell::Character(character) => character.to_string(),
+            GridCell::Delete => "DEL".to_string(),
+            GridCell::Confirm => "OK".to_string(),
+        }
+    }
+}
+
+/// Starts editing of the focused input with confirm or a click
+pub fn text_input_focus_system(
+    actions: Res<Input<GameAction>>,
+    state: Res<ButtonState>,
+    nodes: Query<&Parent, With<SelectedByDefault>>,
+    interaction_query: Query<(&Interaction, &Parent), Changed<Interaction>>,
+    mut inputs: Query<&mut CMUITextInput>,
+) {
+    let mut started: Vec<Entity> = interaction_query
+        .iter()
+        .filter(|(interaction, _)| **interaction == Interaction::Clicked)
+        .map(|(_, parent)| parent.0)
+        .collect();
+
+    if actions.just_pressed(GameAction::Confirm) {
+        started.extend(state.focused_control(&nodes));
+    }
+
+    if inputs.iter().any(|input| input.is_editing) { return; }
+
+    for entity in started {
+        if let Ok(mut input) = inputs.get_mut(entity) {
+            input.start_editing();
+
+            break;
+        }
+    }
+}
+
+/// Edits the input with keyboard and [`CharacterGrid`]. Runs after
+/// [`ActionSystem`](crate::controls::ActionSystem) and swallows actions,
+/// so typed keys don't navigate the menu
+#[allow(clippy::too_many_arguments)]
+pub fn text_input_editing_system(
+    keyboard: Res<Input<KeyCode>>,
+    gamepad: Res<ActiveGamepad>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    mut characters: EventReader<ReceivedCharacter>,
+    mut actions: ResMut<Input<GameAction>>,
+    mut inputs: Query<&mut CMUITextInput>,
+    mut grids: Query<&mut CharacterGrid>,
+    mut events: EventWriter<TextInputConfirmEvent>,
+) {
+    let mut input = match inputs.iter_mut().find(|input| input.is_editing) {
+        Some(input) => input,
+        None => {
+            // Characters typed outside of editing must not show up later
+            for _ in characters.iter() {}
+
+            return;
+        }
+    };
+
+    for action in GameAction::iter() {
+        actions.clear_just_pressed(action);
+    }
+
+    for event in characters.iter() {
+        input.insert(event.char);
+    }
+
+    let pressed = |button_type| gamepad.just_pressed(&gamepad_buttons, button_type);
+
+    if keyboard.just_pressed(KeyCode::Back) || pressed(GamepadButtonType::West) {
+        input.backspace();
+    }
+
+    if keyboard.just_pressed(KeyCode::Delete) {
+        input.delete();
+    }
+
+    if keyboard.just_pressed(KeyCode::Left) {
+        let caret = input.caret.saturating_sub(1);
+
+        input.move_caret(caret);
+    }
+
+    if keyboard.just_pressed(KeyCode::Right) {
+        let caret = input.caret + 1;
+
+        input.move_caret(caret);
+    }
+
+    if keyboard.just_pressed(KeyCode::Home) {
+        input.move_caret(0);
+    }
+
+    if keyboard.just_pressed(KeyCode::End) {
+        input.move_caret(usize::MAX);
+    }
+
+    let mut is_confirmed = keyboard.any_just_pressed([KeyCode::Return, KeyCode::NumpadEnter])
+        || pressed(GamepadButtonType::Start);
+
+    if let Ok(mut grid) = grids.get_single_mut() {
+        let dx = pressed(GamepadButtonType::DPadRight) as isize - pressed(GamepadButtonType::DPadLeft) as isize;
+        let dy = pressed(GamepadButtonType::DPadDown) as isize - pressed(GamepadButtonType::DPadUp) as isize;
+
+        if dx != 0 || dy != 0 {
+            grid.navigate(dx, dy);
+        }
+
+        if pressed(GamepadButtonType::South) {
+            match CharacterGrid::cells()[grid.index] {
+                GridCell::Character(character) => input.insert(character),
+                GridCell::Delete => input.backspace(),
+                GridCell::Confirm => is_confirmed = true,
+            }
+        }
+    }
+
+    if is_confirmed {
+        events.send(input.confirm());
+    } else if keyboard.just_pressed(KeyCode::Escape) || pressed(GamepadButtonType::East) {
+        input.cancel();
+    }
+}
+
+/// Shows [`CharacterGrid`] while any input is being edited
+pub fn character_grid_system(
+    theme: Res<UiTheme>,
+    inputs: Query<&CMUITextInput>,
+    grids: Query<Entity, With<CharacterGrid>>,
+    mut commands: Commands,
+) {
+    let is_editing = inputs.iter().any(|input| input.is_editing);
+
+    if !is_editing {
+        for grid in grids.iter() {
+            commands.entity(grid).despawn_recursive();
+        }
+
+        return;
+    }
+
+    if !grids.is_empty() { return; }
+
+    let cells = CharacterGrid::cells();
+
+    commands.spawn()
+        .insert(CharacterGrid { index: 0 })
+        .insert_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect { bottom: Val::Percent(5.0), left: Val::Percent(5.0), ..default() },
+                flex_direction: FlexDirection::ColumnReverse,
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .with_children(|grid| {
+            for (row, row_cells) in cells.chunks(CharacterGrid::COLUMNS).enumerate() {
+                grid.spawn_bundle(NodeBundle {
+                    style: Style { flex_direction: FlexDirection::Row, ..default() },
+                    color: Color::NONE.into(),
+                    ..default()
+                })
+                    .with_children(|row_node| {
+                        for (column, cell) in row_cells.iter().enumerate() {
+                            row_node.spawn()
+                                .insert(CharacterGridCell(row * CharacterGrid::COLUMNS + column))
+                                .insert_bundle(NodeBundle {
+                                    style: Style {
+                                        size: Size::new(theme.px(CharacterGrid::CELL_SIZE), theme.px(CharacterGrid::CELL_SIZE)),
+                                        justify_content: JustifyContent::Center,
+                                        align_items: AlignItems::Center,
+                                        ..default()
+                                    },
+                                    color: Color::NONE.into(),
+                                    ..default()
+                                })
+                                .with_children(|cell_node| {
+                                    cell_node.spawn_bundle(TextBundle {
+                                        text: Text::with_section(
+                                            cell.label(),
+                                            TextStyle {
+                                                font: theme.font.clone(),
+                                                font_size: theme.description_font_size,
+                                                color: theme.text_color,
+                                            },
+                                            default()
+                                        ),
+                                        ..default()
+                                    });
+                                });
+                        }
+                    });
+            }
+        });
+}
+
+pub fn character_grid_highlight_system(
+    theme: Res<UiTheme>,
+    grids: Query<&CharacterGrid, Changed<CharacterGrid>>,
+    mut cells: Query<(&CharacterGridCell, &mut UiColor)>,
+) {
+    if let Ok(grid) = grids.get_single() {
+        for (cell, mut color) in cells.iter_mut() {
+            *color = if cell.0 == grid.index { theme.active_color.into() } else { Color::NONE.into() };
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use bevy::prelude::*;
+
+use crate::{controls::GameAction, localization::Localization};
+
+use super::button::{ButtonState, FocusableControl, SelectedByDefault};
+
+/// On/off switch. Flipped with confirm, left/right menu actions or mouse click
+#[derive(Component, Clone)]
+pub struct CMUIToggle {
+    pub key: String,
+    pub text: String,
+    pub value: bool,
+    pub is_selected_by_default: bool,
+}
+
+/// Value of [`CMUIToggle`] with the key has been changed by the player
+pub struct ToggleChangedEvent {
+    pub key: String,
+    pub value: bool,
+}
+
+impl CMUIToggle {
+    pub fn new<T: ToString, S: ToString>(key: T, text: S, value: bool) -> Self {
+        CMUIToggle { key: key.to_string(), text: text.to_string(), value, is_selected_by_default: false }
+    }
+
+    pub fn selected(mut self) -> Self {
+        self.is_selected_by_default = true;
+
+        self
+    }
+}
+
+impl FocusableControl for CMUIToggle {
+    fn key(&self) -> &str {
+        &self.key
+    }
+
+    fn text(&self, localization: &Localization) -> String {
+        let value = localization.get(if self.value { "ui.on" } else { "ui.off" });
+
+        format!("{}: {}", localization.get(&self.text), value)
+    }
+
+    fn is_selected_by_default(&self) -> bool {
+        self.is_selected_by_default
+    }
+}
+
+pub fn toggle_input_system(
+    actions: Res<Input<GameAction>>,
+    state: Res<ButtonState>,
+    nodes: Query<&Parent, With<SelectedByDefault>>,
+    interaction_query: Query<(&Interaction, &Parent), Changed<Interaction>>,
+    mut toggles: Query<&mut CMUIToggle>,
+    mut events: EventWriter<ToggleChangedEvent>,
+) {
+    let mut flipped: Vec<Entity> = interaction_query
+        .iter()
+        .filter(|(interaction, _)| **interaction == Interaction::Clicked)
+        .map(|(_, parent)| parent.0)
+        .collect();
+
+    if actions.any_just_pressed([GameAction::Confirm, GameAction::MenuLeft, GameAction::MenuRight]) {
+        flipped.extend(state.focused_control(&nodes));
+    }
+
+    for entity in flipped {
+        if let Ok(mut toggle) = toggles.get_mut(entity) {
+            toggle.value = !toggle.value;
+            events.send(ToggleChangedEvent { key: toggle.key.clone(), value: toggle.value });
+        }
+    }
+}
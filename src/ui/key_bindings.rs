@@ -0,0 +1,252 @@
+use bevy::{prelude::*, input::{keyboard::KeyboardInput, mouse::MouseButtonInput, ElementState}};
+use iyes_loopless::prelude::*;
+use strum::IntoEnumIterator;
+use strum_macros::Display;
+
+use crate::{
+    GameState,
+    helpers::despawn_with,
+    controls::{ActionGroup, ActionSystem, ActiveGamepad, Binding, GameAction, KeyMap},
+    localization::Localization,
+    assets::theme::UiTheme,
+};
+
+use super::controls::{ButtonPressEvent, menu::CMUIMenu, button::CMUIButton};
+
+/// Screen to rebind [`GameAction`]s. Selected action waits for the next
+/// key or button press, which is saved to [`KeyMap`] unless it's already used
+#[derive(Default)]
+pub struct KeyBindingsPlugin;
+
+impl Plugin for KeyBindingsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<KeyBindingsScreen>()
+            .add_enter_system(GameState::KeyBindings, enter_system)
+            .add_system(handle_menu_click_system.run_in_state(GameState::KeyBindings))
+            .add_system(bindings_text_system.run_in_state(GameState::KeyBindings))
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                capture_binding_system.run_in_state(GameState::KeyBindings).after(ActionSystem)
+            )
+            .add_exit_system(GameState::KeyBindings, despawn_with::<CMUIMenu>)
+            .add_exit_system(GameState::KeyBindings, despawn_with::<BindingsText>);
+    }
+}
+
+struct KeyBindingsScreen {
+    page: ActionGroup,
+    /// Action waiting for a new binding
+    waiting: Option<GameAction>,
+    status: Option<BindingStatus>,
+}
+
+/// Result of the last rebinding, localized when the text is shown
+enum BindingStatus {
+    Waiting(GameAction),
+    Conflict(Binding, GameAction),
+    Bound(Binding, GameAction),
+}
+
+impl BindingStatus {
+    fn text(&self, localization: &Localization) -> String {
+        match self {
+            BindingStatus::Waiting(action) => localization.format(
+                "controls.press_key",
+                &[("action", &localization.get(action.label()))]
+            ),
+            BindingStatus::Conflict(binding, other) => localization.format(
+                "controls.conflict",
+                &[("binding", binding), ("action", &localization.get(other.label()))]
+            ),
+            BindingStatus::Bound(binding, action) => localization.format(
+                "controls.bound",
+                &[("binding", binding), ("action", &localization.get(action.label()))]
+            ),
+        }
+    }
+}
+
+impl Default for KeyBindingsScreen {
+    fn default() -> Self {
+        Self { page: ActionGroup::Claw, waiting: None, status: None }
+    }
+}
+
+#[derive(Component)]
+struct BindingsText;
+
+#[derive(Display)]
+enum KeyBindingsButton {
+    Page,
+    Back
+}
+
+fn spawn_menu(commands: &mut Commands, page: ActionGroup) {
+    let mut buttons: Vec<CMUIButton> = page
+        .actions()
+        .map(|action| CMUIButton::new(action, action.label()))
+        .collect();
+
+    buttons.push(CMUIButton::new(
+        KeyBindingsButton::Page,
+        match page {
+            ActionGroup::Claw => "controls.menu_keys",
+            ActionGroup::Menu => "controls.claw_keys",
+        }
+    ));
+    buttons.push(CMUIButton::new(KeyBindingsButton::Back, "ui.back"));
+    buttons[0] = buttons[0].clone().selected();
+
+    commands.spawn()
+        .insert(CMUIMenu::new("controls.title", buttons));
+}
+
+fn enter_system(
+    theme: Res<UiTheme>,
+    mut screen: ResMut<KeyBindingsScreen>,
+    mut commands: Commands,
+) {
+    *screen = KeyBindingsScreen::default();
+
+    spawn_menu(&mut commands, screen.page);
+
+    commands.spawn()
+        .insert(BindingsText)
+        .insert_bundle(TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect { top: Val::Percent(30.0), left: Val::Percent(10.0), ..default() },
+                ..default()
+            },
+            text: Text::with_section(
+                "",
+                TextStyle {
+                    font: theme.font.clone(),
+                    font_size: theme.text_font_size,
+                    color: theme.text_color,
+                },
+                default()
+            ),
+            ..default()
+        });
+}
+
+fn handle_menu_click_system(
+    mut screen: ResMut<KeyBindingsScreen>,
+    mut events: EventReader<ButtonPressEvent>,
+    menu_query: Query<Entity, With<CMUIMenu>>,
+    mut commands: Commands,
+) {
+    for event in events.iter() {
+        if event.0 == KeyBindingsButton::Back.to_string() {
+            commands.insert_resource(NextState(GameState::MainMenu));
+        } else if event.0 == KeyBindingsButton::Page.to_string() {
+            screen.page = match screen.page {
+                ActionGroup::Claw => ActionGroup::Menu,
+                ActionGroup::Menu => ActionGroup::Claw,
+            };
+            screen.status = None;
+
+            for menu in menu_query.iter() {
+                commands.entity(menu).despawn_recursive();
+            }
+
+            spawn_menu(&mut commands, screen.page);
+        } else if let Some(action) = GameAction::iter().find(|action| action.to_string() == event.0) {
+            screen.waiting = Some(action);
+            screen.status = Some(BindingStatus::Waiting(action));
+        }
+    }
+}
+
+fn capture_binding_system(
+    gamepad: Res<ActiveGamepad>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    mut keyboard_events: EventReader<KeyboardInput>,
+    mut mouse_events: EventReader<MouseButtonInput>,
+    mut actions: ResMut<Input<GameAction>>,
+    mut key_map: ResMut<KeyMap>,
+    mut screen: ResMut<KeyBindingsScreen>,
+) {
+    let mut is_cancelled = false;
+    // Events are read every frame, so the press that selected the action isn't captured
+    let mut bindings: Vec<Binding> = keyboard_events
+        .iter()
+        .filter(|event| event.state == ElementState::Pressed)
+        .filter_map(|event| {
+            if event.key_code == Some(KeyCode::Escape) {
+                is_cancelled = true;
+
+                None
+            } else {
+                Some(Binding::from_keyboard(event))
+            }
+        })
+        .collect();
+
+    bindings.extend(
+        mouse_events
+            .iter()
+            .filter(|event| event.state == ElementState::Pressed)
+            .map(|event| Binding::Mouse(event.button))
+    );
+    bindings.extend(
+        gamepad_buttons
+            .get_just_pressed()
+            .filter(|button| Some(button.0) == gamepad.0)
+            .map(|button| Binding::Gamepad(button.1))
+    );
+
+    if let Some(action) = screen.waiting {
+        // Pressed key must not navigate the menu
+        for action in GameAction::iter() {
+            actions.clear_just_pressed(action);
+        }
+
+        if is_cancelled {
+            screen.waiting = None;
+            screen.status = None;
+        } else if let Some(binding) = bindings.first().copied() {
+            screen.waiting = None;
+            screen.status = Some(match key_map.conflict(action, binding) {
+                Some(other) => BindingStatus::Conflict(binding, other),
+                None => {
+                    key_map.rebind(action, binding);
+                    key_map.save();
+
+                    BindingStatus::Bound(binding, action)
+                }
+            });
+        }
+    }
+}
+
+fn bindings_text_system(
+    key_map: Res<KeyMap>,
+    screen: Res<KeyBindingsScreen>,
+    localization: Res<Localization>,
+    mut query: Query<&mut Text, With<BindingsText>>,
+) {
+    if !key_map.is_changed() && !screen.is_changed() && !localization.is_changed() { return; }
+
+    if let Ok(mut text) = query.get_single_mut() {
+        let mut lines: Vec<String> = screen.page
+            .actions()
+            .map(|action| {
+                let bindings: Vec<String> = key_map
+                    .bindings(action)
+                    .iter()
+                    .map(Binding::to_string)
+                    .collect();
+
+                format!("{}: {}", localization.get(action.label()), bindings.join(", "))
+            })
+            .collect();
+
+        lines.push(String::new());
+        lines.push(screen.status.as_ref().map(|status| status.text(&localization)).unwrap_or_default());
+
+        text.sections[0].value = lines.join("\n");
+    }
+}
@@ -0,0 +1,179 @@
+use bevy::{prelude::*, app::AppExit, asset::LoadState};
+use iyes_loopless::prelude::*;
+use strum_macros::Display;
+
+use crate::{
+    GameState,
+    helpers::despawn_with,
+    localization::Localization,
+    assets::{AssetsLoading, theme::UiTheme},
+};
+
+use super::controls::{ButtonPressEvent, menu::CMUIMenu, button::CMUIButton};
+
+/// Progress of [`AssetsLoading`] with the name of the pending asset.
+/// Essential assets that failed to load are listed with an option to load them again
+#[derive(Default)]
+pub struct LoadingScreenPlugin;
+
+impl Plugin for LoadingScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_startup_system(setup_system)
+            .add_system(progress_system.run_in_state(GameState::Loading))
+            .add_system(handle_menu_click_system.run_in_state(GameState::Loading))
+            .add_exit_system(GameState::Loading, despawn_with::<LoadingScreen>)
+            .add_exit_system(GameState::Loading, despawn_with::<CMUIMenu>);
+    }
+}
+
+#[derive(Component)]
+struct LoadingScreen;
+
+#[derive(Component)]
+struct LoadingText;
+
+#[derive(Component)]
+struct LoadingBar;
+
+#[derive(Display)]
+enum LoadingButton {
+    Retry,
+    Quit,
+}
+
+const BAR_WIDTH: f32 = 500.0;
+const BAR_HEIGHT: f32 = 20.0;
+const BAR_BACKGROUND: Color = Color::rgba(0.0, 0.0, 0.0, 0.3);
+
+fn setup_system(theme: Res<UiTheme>, mut commands: Commands) {
+    // Takes the left half, so the error menu on the right isn't covered
+    commands.spawn()
+        .insert(LoadingScreen)
+        .insert_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect { top: Val::Px(0.0), left: Val::Px(0.0), ..default() },
+                size: Size::new(Val::Percent(50.0), Val::Percent(100.0)),
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: theme.shadow_color.into(),
+            ..default()
+        })
+        .with_children(|screen| {
+            screen.spawn()
+                .insert(LoadingText)
+                .insert_bundle(TextBundle {
+                    style: Style {
+                        max_size: Size::new(theme.px(BAR_WIDTH), Val::Undefined),
+                        ..default()
+                    },
+                    text: Text::with_section(
+                        "",
+                        TextStyle {
+                            font: theme.font.clone(),
+                            font_size: theme.text_font_size,
+                            color: theme.text_color,
+                        },
+                        default()
+                    ),
+                    ..default()
+                });
+
+            screen.spawn_bundle(NodeBundle {
+                style: Style {
+                    size: Size::new(theme.px(BAR_WIDTH), theme.px(BAR_HEIGHT)),
+                    margin: Rect { top: theme.px(20.0), ..default() },
+                    ..default()
+                },
+                color: BAR_BACKGROUND.into(),
+                ..default()
+            })
+                .with_children(|bar| {
+                    bar.spawn()
+                        .insert(LoadingBar)
+                        .insert_bundle(NodeBundle {
+                            style: Style {
+                                size: Size::new(Val::Percent(0.0), Val::Percent(100.0)),
+                                ..default()
+                            },
+                            color: theme.text_color.into(),
+                            ..default()
+                        });
+                });
+        });
+}
+
+fn progress_system(
+    server: Res<AssetServer>,
+    loading: Option<Res<AssetsLoading>>,
+    localization: Res<Localization>,
+    menus: Query<Entity, With<CMUIMenu>>,
+    mut texts: Query<&mut Text, With<LoadingText>>,
+    mut bars: Query<&mut Style, With<LoadingBar>>,
+    mut commands: Commands,
+) {
+    let loading = match loading {
+        Some(loading) => loading,
+        None => return,
+    };
+    let status = loading.status(&server);
+
+    if let Ok(mut style) = bars.get_single_mut() {
+        style.size.width = Val::Percent(status.progress * 100.0);
+    }
+
+    if let Ok(mut text) = texts.get_single_mut() {
+        text.sections[0].value = if status.failed.is_empty() {
+            let pending = status.pending.map(|asset| asset.describe(&server)).unwrap_or_default();
+
+            format!("{:.0}%\n{}", status.progress * 100.0, pending)
+        } else {
+            let failed: Vec<String> = status.failed.iter().map(|asset| asset.describe(&server)).collect();
+
+            format!("{}\n{}", localization.get("loading.failed"), failed.join("\n"))
+        };
+    }
+
+    if !status.failed.is_empty() && menus.is_empty() {
+        commands.spawn()
+            .insert(CMUIMenu::new("loading.error", vec![
+                CMUIButton::new(LoadingButton::Retry, "loading.retry").selected(),
+                CMUIButton::new(LoadingButton::Quit, "menu.quit"),
+            ]));
+    } else if status.failed.is_empty() {
+        for menu in menus.iter() {
+            commands.entity(menu).despawn_recursive();
+        }
+    }
+}
+
+fn handle_menu_click_system(
+    server: Res<AssetServer>,
+    loading: Option<ResMut<AssetsLoading>>,
+    mut events: EventReader<ButtonPressEvent>,
+    mut app_exit_events: EventWriter<AppExit>,
+) {
+    let mut loading = match loading {
+        Some(loading) => loading,
+        None => return,
+    };
+
+    for event in events.iter() {
+        if event.0 == LoadingButton::Retry.to_string() {
+            // Failed assets aren't cached, so loading the path again starts over
+            for asset in loading.0.iter_mut() {
+                if server.get_load_state(&asset.handle) != LoadState::Failed { continue; }
+
+                if let Some(path) = server.get_handle_path(&asset.handle) {
+                    asset.handle = server.load_untyped(path);
+                }
+            }
+        } else if event.0 == LoadingButton::Quit.to_string() {
+            app_exit_events.send(AppExit);
+        }
+    }
+}
@@ -5,9 +5,14 @@ use strum_macros::Display;
 
 use crate::{
     GameState,
+    daily::{self, DailyRecords},
     helpers::despawn_with,
     assets::audio::{BackgroundAudioChannel, AudioHandleStorage, AudioCollection},
-    gamemodes::gameplay::{Gamemode, GameSettings},
+    gamemodes::{gameplay::GameSettings, definition::GamemodeRegistry},
+    replay::{Replay, PendingReplay},
+    high_scores::HighScores,
+    localization::Localization,
+    settings::Settings,
 };
 
 use super::controls::{*, menu::CMUIMenu, button::CMUIButton};
@@ -27,49 +32,106 @@ impl Plugin for MenuPlugin {
 
 #[derive(PartialEq, Eq, Hash, Display)]
 enum MenuButton {
-    SpeedGame,
-    NumberGame,
+    Daily,
+    Replay,
+    HighScores,
+    PrizeCounter,
+    KeyBindings,
+    Settings,
     Quit
 }
 
 fn main_menu_system(
     audio: Res<AudioChannel<BackgroundAudioChannel>>,
     audio_storage: Res<AudioHandleStorage>,
+    registry: Res<GamemodeRegistry>,
+    high_scores: Res<HighScores>,
+    daily_records: Res<DailyRecords>,
+    localization: Res<Localization>,
     mut commands: Commands
 ) {
     if let Some(music) = audio_storage.0.get(&AudioCollection::Background1) {
         audio.play_looped(music.clone());
     }
 
+    let mut buttons: Vec<CMUIButton> = registry
+        .iter()
+        .map(|entry| {
+            // Variant is unlocked by winning the required one, which puts it to the high score table
+            let required = entry.params.requires
+                .as_ref()
+                .filter(|required| high_scores.table(required).is_empty())
+                .map(|required| registry.get(required).map_or(required.as_str(), |entry| entry.params.label.as_str()));
+
+            CMUIButton::new(&entry.id, &entry.params.label)
+                .disabled(required.is_some())
+                .with_description(match required {
+                    Some(required) => localization.format("menu.locked", &[("name", &localization.get(required))]),
+                    None => entry.params.description(&localization),
+                })
+        })
+        .collect();
+
+    buttons.push(daily::menu_button(MenuButton::Daily, &registry, &daily_records, &localization));
+
+    buttons.push(
+        CMUIButton::new(MenuButton::Replay, "menu.replay")
+            .hidden(!Replay::last_exists())
+            .with_description("menu.replay.description")
+    );
+
+    buttons.push(CMUIButton::new(MenuButton::HighScores, "menu.high_scores"));
+    buttons.push(
+        CMUIButton::new(MenuButton::PrizeCounter, "menu.prize_counter")
+            .with_description("menu.prize_counter.description")
+    );
+    buttons.push(CMUIButton::new(MenuButton::Settings, "menu.settings"));
+    buttons.push(CMUIButton::new(MenuButton::KeyBindings, "menu.controls"));
+    buttons.push(CMUIButton::new(MenuButton::Quit, "menu.quit"));
+
+    // Locked variants come first when the earlier ones are not won yet
+    let first = buttons.iter().position(|button| !button.is_disabled && !button.is_hidden).unwrap_or(0);
+
+    buttons[first] = buttons[first].clone().selected();
+
     commands.spawn()
-        .insert(CMUIMenu {
-            title: "Menu".to_string(),
-            buttons: vec![
-                CMUIButton::new(MenuButton::SpeedGame, "Speed game").selected(),
-                CMUIButton::new(MenuButton::NumberGame, "Number game"),
-                CMUIButton::new(MenuButton::Quit, "Quit"),
-            ]
-        });
+        .insert(CMUIMenu::new("menu.title", buttons));
 }
 
 fn handle_menu_click_system(
+    registry: Res<GamemodeRegistry>,
+    user_settings: Res<Settings>,
     mut settings: ResMut<GameSettings>,
+    mut pending_replay: ResMut<PendingReplay>,
     mut events: EventReader<ButtonPressEvent>,
     mut app_exit_events: EventWriter<AppExit>,
     mut commands: Commands
 ) {
     for event in events.iter() {
-        settings.gamemode = if event.0 == MenuButton::SpeedGame.to_string() {
-            Gamemode::SpeedGame
-        } else if event.0 == MenuButton::NumberGame.to_string() {
-            Gamemode::NumberGame
-        } else {
-            app_exit_events.send(AppExit);
-
-            Gamemode::None
-        };
+        if let Some(entry) = registry.get(&event.0) {
+            settings.gamemode = entry.gamemode;
+            settings.control_scheme = user_settings.control_scheme;
+            settings.seed = None;
+            settings.is_playback = false;
+            settings.variant = entry.id.clone();
+            settings.params = Some(entry.params.clone());
 
-        commands.insert_resource(NextState(GameState::InGame));
+            commands.insert_resource(NextState(GameState::InGame));
+        } else if event.0 == MenuButton::Daily.to_string() {
+            daily::start_today(&registry, &user_settings, &mut settings, &mut commands);
+        } else if event.0 == MenuButton::Replay.to_string() {
+            pending_replay.0 = Replay::load_last();
+        } else if event.0 == MenuButton::HighScores.to_string() {
+            commands.insert_resource(NextState(GameState::HighScores));
+        } else if event.0 == MenuButton::PrizeCounter.to_string() {
+            commands.insert_resource(NextState(GameState::PrizeCounter));
+        } else if event.0 == MenuButton::Settings.to_string() {
+            commands.insert_resource(NextState(GameState::Settings));
+        } else if event.0 == MenuButton::KeyBindings.to_string() {
+            commands.insert_resource(NextState(GameState::KeyBindings));
+        } else if event.0 == MenuButton::Quit.to_string() {
+            app_exit_events.send(AppExit);
+        }
     }
 }
 
@@ -77,28 +139,3 @@ fn handle_menu_click_system(
 fn stop_music(audio: Res<AudioChannel<BackgroundAudioChannel>>) {
     audio.stop();
 }
-
-// fn main_menu_system(
-//     keyboard_input: Res<Input<KeyCode>>,
-//     mut windows: ResMut<Windows>,
-//     mut state: ResMut<State<GameState>>,
-// ) {
-    // if keyboard_input.just_pressed(KeyCode::Escape) {
-    //     let window = windows.get_primary_mut().unwrap();
-
-    //     match state.current() {
-    //         GameState::Play => {
-    //             if let Ok(()) = state.set(GameState::Pause) {
-    //                 window.toggle_cursor(false);
-    //             }
-    //             println!("pausing");
-    //         }
-    //         GameState::Pause => {
-    //             if let Ok(()) = state.set(GameState::Play) {
-    //                 window.toggle_cursor(true);
-    //             }
-    //             println!("playing");
-    //         }
-    //     }
-    // }
-// }
\ No newline at end of file
@@ -1,9 +1,19 @@
 use bevy::{prelude::*, app::PluginGroupBuilder};
 
-use self::{main_menu::MenuPlugin, system_info::SystemInfoPlugin, controls::ControlsPlugin};
+use self::{
+    main_menu::MenuPlugin,
+    system_info::SystemInfoPlugin,
+    controls::ControlsPlugin,
+    key_bindings::KeyBindingsPlugin,
+    settings_menu::SettingsMenuPlugin,
+    loading_screen::LoadingScreenPlugin,
+};
 
 pub mod controls;
 mod main_menu;
+mod key_bindings;
+mod settings_menu;
+mod loading_screen;
 mod system_info;
 
 #[derive(Default)]
@@ -15,6 +25,9 @@ impl PluginGroup for UIPlugins {
             .add(UIMainPlugin)
             .add(ControlsPlugin)
             .add(MenuPlugin)
+            .add(KeyBindingsPlugin)
+            .add(SettingsMenuPlugin)
+            .add(LoadingScreenPlugin)
             .add(SystemInfoPlugin);
     }
 }
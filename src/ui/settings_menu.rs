@@ -0,0 +1,312 @@
+use std::ops::RangeInclusive;
+
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+use strum::IntoEnumIterator;
+use strum_macros::{Display, EnumIter};
+
+use crate::{
+    GameState,
+    helpers::despawn_with,
+    settings::{Settings, SelectionPalette, ControlScheme},
+    localization::Localization,
+    assets::theme::UiTheme,
+    arcade::ArcadeWallet,
+};
+
+use super::controls::{
+    ButtonPressEvent, SliderChangedEvent, ToggleChangedEvent, SelectChangedEvent, TextInputConfirmEvent,
+    menu::{CMUIMenu, CMUIMenuControl, MenuStackEvent},
+    button::CMUIButton,
+    slider::CMUISlider,
+    toggle::CMUIToggle,
+    select::CMUISelect,
+    text_input::CMUITextInput,
+};
+
+/// Settings screen with a page of sliders, toggles and selects for each group of options.
+/// Changes apply immediately and are saved when the screen is left
+#[derive(Default)]
+pub struct SettingsMenuPlugin;
+
+impl Plugin for SettingsMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_enter_system(GameState::Settings, enter_system)
+            .add_system(handle_menu_click_system.run_in_state(GameState::Settings))
+            .add_system(handle_control_change_system.run_in_state(GameState::Settings))
+            .add_exit_system(GameState::Settings, despawn_with::<CMUIMenu>)
+            .add_exit_system(GameState::Settings, save_system);
+    }
+}
+
+#[derive(Display, EnumIter, Clone, Copy, PartialEq)]
+enum SettingsPage {
+    Audio,
+    Video,
+    Gameplay,
+    Accessibility,
+}
+
+#[derive(Display, EnumIter, Clone, Copy, PartialEq)]
+enum SettingsItem {
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    UiVolume,
+    Msaa,
+    Fullscreen,
+    Vsync,
+    ShowFps,
+    Theme,
+    CameraSensitivity,
+    PlayerName,
+    Language,
+    ControlScheme,
+    UiScale,
+    HighContrast,
+    ReducedMotion,
+    Palette,
+}
+
+#[derive(Display)]
+enum SettingsButton {
+    Back
+}
+
+impl SettingsPage {
+    fn items(&self) -> impl Iterator<Item = SettingsItem> + '_ {
+        SettingsItem::iter().filter(move |item| item.page() == *self)
+    }
+
+    /// Localization key of the page name
+    fn title(&self) -> &'static str {
+        match self {
+            SettingsPage::Audio => "settings.audio",
+            SettingsPage::Video => "settings.video",
+            SettingsPage::Gameplay => "settings.gameplay",
+            SettingsPage::Accessibility => "settings.accessibility",
+        }
+    }
+
+    /// `themes` are codes and names of the available themes
+    fn menu(&self, settings: &Settings, localization: &Localization, themes: &[(String, String)]) -> CMUIMenu {
+        let mut controls: Vec<CMUIMenuControl> = self
+            .items()
+            .map(|item| item.control(settings, localization, themes))
+            .collect();
+
+        controls.push(CMUIButton::back().into());
+        controls[0] = controls[0].clone().selected();
+
+        CMUIMenu::with_controls(self.title(), controls)
+    }
+}
+
+impl SettingsItem {
+    const VOLUME_STEP: f32 = 0.1;
+    const SENSITIVITY_STEP: f32 = 0.25;
+    const SENSITIVITY_RANGE: RangeInclusive<f32> = 0.25..=2.0;
+    const UI_SCALE_STEP: f32 = 0.25;
+    const MSAA_OPTIONS: [&'static str; 2] = ["ui.off", "4x"];
+
+    fn page(&self) -> SettingsPage {
+        match self {
+            SettingsItem::MasterVolume
+            | SettingsItem::MusicVolume
+            | SettingsItem::SfxVolume
+            | SettingsItem::UiVolume => SettingsPage::Audio,
+            SettingsItem::Msaa
+            | SettingsItem::Fullscreen
+            | SettingsItem::Vsync
+            | SettingsItem::ShowFps
+            | SettingsItem::Theme => SettingsPage::Video,
+            SettingsItem::CameraSensitivity
+            | SettingsItem::PlayerName
+            | SettingsItem::Language
+            | SettingsItem::ControlScheme => SettingsPage::Gameplay,
+            SettingsItem::UiScale
+            | SettingsItem::HighContrast
+            | SettingsItem::ReducedMotion
+            | SettingsItem::Palette => SettingsPage::Accessibility,
+        }
+    }
+
+    fn control(&self, settings: &Settings, localization: &Localization, themes: &[(String, String)]) -> CMUIMenuControl {
+        let volume = |text: &str, value: f32| -> CMUIMenuControl {
+            CMUISlider::new(self, text, value, 0.0..=1.0, SettingsItem::VOLUME_STEP).percent().into()
+        };
+
+        match self {
+            SettingsItem::MasterVolume => volume("settings.volume", settings.volume.master),
+            SettingsItem::MusicVolume => volume("settings.music", settings.volume.music),
+            SettingsItem::SfxVolume => volume("settings.sfx", settings.volume.sfx),
+            SettingsItem::UiVolume => volume("settings.ui_volume", settings.volume.ui),
+            SettingsItem::Msaa => {
+                let index = Settings::MSAA_SAMPLES.iter().position(|samples| *samples == settings.msaa).unwrap_or(0);
+
+                CMUISelect::new(self, "settings.msaa", &SettingsItem::MSAA_OPTIONS, index).into()
+            }
+            SettingsItem::Fullscreen => CMUIToggle::new(self, "settings.fullscreen", settings.fullscreen).into(),
+            SettingsItem::Vsync => CMUIToggle::new(self, "settings.vsync", settings.vsync).into(),
+            SettingsItem::ShowFps => CMUIToggle::new(self, "settings.show_fps", settings.show_fps).into(),
+            SettingsItem::Theme => {
+                let names: Vec<&str> = themes.iter().map(|(_, name)| name.as_str()).collect();
+                let index = themes.iter().position(|(code, _)| *code == settings.theme).unwrap_or(0);
+
+                CMUISelect::new(self, "settings.theme", &names, index).into()
+            }
+            SettingsItem::CameraSensitivity => CMUISlider::new(
+                self,
+                "settings.camera",
+                settings.camera_sensitivity,
+                SettingsItem::SENSITIVITY_RANGE,
+                SettingsItem::SENSITIVITY_STEP
+            )
+                .with_format(|value| format!("{:.2}x", value))
+                .into(),
+            SettingsItem::PlayerName => CMUITextInput::new(
+                self,
+                "settings.player_name",
+                &settings.player_name,
+                Settings::PLAYER_NAME_LENGTH
+            ).into(),
+            SettingsItem::Language => {
+                let languages = localization.languages();
+                let names: Vec<&str> = languages.iter().map(|(_, name)| name.as_str()).collect();
+                let index = languages.iter().position(|(code, _)| *code == settings.language).unwrap_or(0);
+
+                CMUISelect::new(self, "settings.language", &names, index).into()
+            }
+            SettingsItem::ControlScheme => {
+                let schemes: Vec<&str> = ControlScheme::iter().map(|scheme| scheme.label()).collect();
+                let index = ControlScheme::iter().position(|scheme| scheme == settings.control_scheme).unwrap_or(0);
+
+                CMUISelect::new(self, "settings.control_scheme", &schemes, index).into()
+            }
+            SettingsItem::UiScale => CMUISlider::new(
+                self,
+                "settings.ui_scale",
+                settings.accessibility.ui_scale,
+                Settings::UI_SCALE_RANGE,
+                SettingsItem::UI_SCALE_STEP
+            ).percent().into(),
+            SettingsItem::HighContrast => CMUIToggle::new(
+                self,
+                "settings.high_contrast",
+                settings.accessibility.high_contrast
+            ).into(),
+            SettingsItem::ReducedMotion => CMUIToggle::new(
+                self,
+                "settings.reduced_motion",
+                settings.accessibility.reduced_motion
+            ).into(),
+            SettingsItem::Palette => {
+                let palettes: Vec<&str> = SelectionPalette::iter().map(|palette| palette.label()).collect();
+                let index = SelectionPalette::iter().position(|palette| palette == settings.accessibility.palette).unwrap_or(0);
+
+                CMUISelect::new(self, "settings.palette", &palettes, index).into()
+            }
+        }
+    }
+
+    fn find(key: &str) -> Option<SettingsItem> {
+        SettingsItem::iter().find(|item| item.to_string() == key)
+    }
+}
+
+fn enter_system(mut commands: Commands) {
+    let mut buttons: Vec<CMUIButton> = SettingsPage::iter().map(|page| CMUIButton::new(page, page.title())).collect();
+
+    buttons.push(CMUIButton::new(SettingsButton::Back, "ui.back"));
+    buttons[0] = buttons[0].clone().selected();
+
+    commands.spawn()
+        .insert(CMUIMenu::new("settings.title", buttons));
+}
+
+fn handle_menu_click_system(
+    settings: Res<Settings>,
+    localization: Res<Localization>,
+    themes: Res<Assets<UiTheme>>,
+    wallet: Res<ArcadeWallet>,
+    mut events: EventReader<ButtonPressEvent>,
+    mut stack_events: EventWriter<MenuStackEvent>,
+    mut commands: Commands,
+) {
+    for event in events.iter() {
+        if event.0 == SettingsButton::Back.to_string() {
+            commands.insert_resource(NextState(GameState::MainMenu));
+        } else if let Some(page) = SettingsPage::iter().find(|page| page.to_string() == event.0) {
+            stack_events.send(MenuStackEvent::Push(page.menu(&settings, &localization, &UiTheme::available(&themes, &wallet.unlocked))));
+        }
+    }
+}
+
+fn handle_control_change_system(
+    mut settings: ResMut<Settings>,
+    localization: Res<Localization>,
+    themes: Res<Assets<UiTheme>>,
+    wallet: Res<ArcadeWallet>,
+    mut slider_events: EventReader<SliderChangedEvent>,
+    mut toggle_events: EventReader<ToggleChangedEvent>,
+    mut select_events: EventReader<SelectChangedEvent>,
+    mut text_events: EventReader<TextInputConfirmEvent>,
+) {
+    for event in slider_events.iter() {
+        match SettingsItem::find(&event.key) {
+            Some(SettingsItem::MasterVolume) => settings.volume.master = event.value,
+            Some(SettingsItem::MusicVolume) => settings.volume.music = event.value,
+            Some(SettingsItem::SfxVolume) => settings.volume.sfx = event.value,
+            Some(SettingsItem::UiVolume) => settings.volume.ui = event.value,
+            Some(SettingsItem::CameraSensitivity) => settings.camera_sensitivity = event.value,
+            Some(SettingsItem::UiScale) => settings.accessibility.ui_scale = event.value,
+            _ => {}
+        }
+    }
+
+    for event in toggle_events.iter() {
+        match SettingsItem::find(&event.key) {
+            Some(SettingsItem::Fullscreen) => settings.fullscreen = event.value,
+            Some(SettingsItem::Vsync) => settings.vsync = event.value,
+            Some(SettingsItem::ShowFps) => settings.show_fps = event.value,
+            Some(SettingsItem::HighContrast) => settings.accessibility.high_contrast = event.value,
+            Some(SettingsItem::ReducedMotion) => settings.accessibility.reduced_motion = event.value,
+            _ => {}
+        }
+    }
+
+    for event in select_events.iter() {
+        match SettingsItem::find(&event.key) {
+            Some(SettingsItem::Msaa) => if let Some(samples) = Settings::MSAA_SAMPLES.get(event.index) {
+                settings.msaa = *samples;
+            },
+            // Text of the screen is switched by the localization as soon as the setting changes
+            Some(SettingsItem::Language) => if let Some((code, _)) = localization.languages().get(event.index) {
+                settings.language = code.clone();
+            },
+            Some(SettingsItem::Theme) => if let Some((code, _)) = UiTheme::available(&themes, &wallet.unlocked).get(event.index) {
+                settings.theme = code.clone();
+            },
+            Some(SettingsItem::ControlScheme) => if let Some(scheme) = ControlScheme::iter().nth(event.index) {
+                settings.control_scheme = scheme;
+            },
+            Some(SettingsItem::Palette) => if let Some(palette) = SelectionPalette::iter().nth(event.index) {
+                settings.accessibility.palette = palette;
+            },
+            _ => {}
+        }
+    }
+
+    for event in text_events.iter() {
+        let name = event.value.trim();
+
+        if SettingsItem::find(&event.key) == Some(SettingsItem::PlayerName) && !name.is_empty() {
+            settings.player_name = name.to_string();
+        }
+    }
+}
+
+fn save_system(settings: Res<Settings>) {
+    settings.save();
+}
@@ -1,5 +1,7 @@
 use bevy::{prelude::*, diagnostic::{Diagnostics, FrameTimeDiagnosticsPlugin}};
 
+use crate::settings::Settings;
+
 #[derive(Default)]
 pub struct SystemInfoPlugin;
 
@@ -11,6 +13,7 @@ impl Plugin for SystemInfoPlugin {
         app
             .add_startup_system(setup_system)
             .add_system(text_update_system)
+            .add_system(visibility_system)
             .add_plugin(FrameTimeDiagnosticsPlugin::default());
     }
 }
@@ -51,3 +54,11 @@ fn text_update_system(diagnostics: Res<Diagnostics>, mut query: Query<&mut Text,
             }
     }
 }
+
+fn visibility_system(settings: Res<Settings>, mut query: Query<&mut Visibility, With<Fps>>) {
+    if !settings.is_changed() { return; }
+
+    for mut visibility in query.iter_mut() {
+        visibility.is_visible = settings.show_fps;
+    }
+}